@@ -0,0 +1,292 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Sampling passes over a text, for tuning a sentence splitter to a corpus
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+
+use crate::{Break, SentenceSplitter, Tokenizer};
+
+/// The quotes looked for by `detect_quotes`
+pub static QUOTE_CANDIDATES: [&str; 7] = ["\"", "'", "`", "”", "’", "»", "«"];
+
+/// How often a quote was seen opening and closing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteCount {
+    /// The quote, one of the `QUOTE_CANDIDATES`
+    pub quote: &'static str,
+    /// Tokens starting with the quote
+    pub opens: usize,
+    /// Tokens ending with the quote
+    pub closes: usize,
+}
+
+impl QuoteCount {
+    /// Returns whether the quote opens about as often as it closes
+    pub fn is_balanced(&self) -> bool {
+        let (low, high) = if self.opens < self.closes {
+            (self.opens, self.closes)
+        } else {
+            (self.closes, self.opens)
+        };
+        low > 0 && high - low <= std::cmp::max(1, high / 5)
+    }
+}
+
+/// The quotes found in a sample of a text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuoteReport {
+    /// The number of tokens sampled
+    pub tokens: usize,
+    /// The counts for every candidate quote
+    pub counts: Vec<QuoteCount>,
+}
+
+impl QuoteReport {
+    /// Returns the balanced quotes, for use with a `SentenceSplitter`
+    pub fn quotes(&self) -> Vec<&'static str> {
+        self.counts.iter().filter(|c| c.is_balanced()).map(|c| c.quote).collect()
+    }
+}
+
+/// Reads at most `limit` tokens from the tokenizer and counts how often each
+/// of the `QUOTE_CANDIDATES` opens and closes a quotation
+///
+/// Each quote is counted on its own, so text quoted like “this” or »this« is
+/// counted, but never reported as balanced. Such quotes can be set with
+/// `SentenceSplitter::set_closing_quote`.
+///
+/// ```
+/// use token::analysis::detect_quotes;
+///
+/// let text = "He said \"no\" and left. She said \"fine, don't\" to the dogs' owner.";
+/// let mut tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+///
+/// let report = detect_quotes(&mut tokenizer, 1000).unwrap();
+/// assert_eq!(vec!["\""], report.quotes());
+/// ```
+pub fn detect_quotes<R: Read>(tokenizer: &mut Tokenizer<R>, limit: usize)
+    -> io::Result<QuoteReport>
+{
+    let mut report = QuoteReport {
+        tokens: 0,
+        counts: QUOTE_CANDIDATES.iter()
+            .map(|q| QuoteCount { quote: q, opens: 0, closes: 0 })
+            .collect(),
+    };
+    while report.tokens < limit {
+        let token = match tokenizer.next()? {
+            Some(token) => token,
+            None => break,
+        };
+        report.tokens += 1;
+        for count in &mut report.counts {
+            if token.starts_with(count.quote) {
+                count.opens += 1;
+            }
+            if token.ends_with(count.quote) {
+                count.closes += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// A token that may be an abbreviation, as it was seen ending in a period
+/// before a lower case word
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbbreviationCandidate {
+    /// The token, with any punctuation before it left out
+    pub token: String,
+    /// How often it was followed by a word starting in lower case
+    pub lowercase: usize,
+    /// How often it was followed by any token
+    pub total: usize,
+}
+
+/// The abbreviation candidates found in a sample of a text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbbreviationReport {
+    /// The number of tokens sampled
+    pub tokens: usize,
+    /// The candidates, the ones most often followed by a lower case word first
+    pub candidates: Vec<AbbreviationCandidate>,
+}
+
+impl AbbreviationReport {
+    /// Returns the candidates followed by a lower case word at least
+    /// `min_count` times, for `SentenceSplitter::set_abbreviations`
+    pub fn abbreviations(&self, min_count: usize) -> Vec<&str> {
+        self.candidates.iter()
+            .filter(|c| c.lowercase >= min_count)
+            .map(|c| c.token.as_str())
+            .collect()
+    }
+}
+
+/// Returns the token without the punctuation before it if it may be an
+/// abbreviation: letters with a period after them, and maybe between them,
+/// with at most `max_len` letters
+fn abbreviation(token: &str, max_len: usize) -> Option<&str> {
+    let token = token.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let letters = token.strip_suffix('.')?;
+    let len = letters.chars().filter(|&c| c != '.').count();
+    let valid = letters.chars().all(|c| c.is_alphabetic() || c == '.')
+        && !letters.starts_with('.');
+    if valid && len > 0 && len <= max_len {
+        Some(token)
+    } else {
+        None
+    }
+}
+
+/// Reads at most `limit` tokens from the tokenizer and ranks the tokens of
+/// at most `max_len` letters that end in a period by how often a word
+/// starting in lower case follows them, which a sentence seldom starts with
+///
+/// ```
+/// use token::analysis::mine_abbreviations;
+///
+/// let text = "Ask approx. ten of them, e.g. the tall ones. Then go. \
+///             It was approx. five. Bring Dr. Watson.";
+/// let mut tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+///
+/// let report = mine_abbreviations(&mut tokenizer, 1000, 6).unwrap();
+/// assert_eq!(vec!["approx.", "e.g."], report.abbreviations(1));
+/// assert_eq!(vec!["approx."], report.abbreviations(2));
+/// ```
+pub fn mine_abbreviations<R: Read>(tokenizer: &mut Tokenizer<R>, limit: usize, max_len: usize)
+    -> io::Result<AbbreviationReport>
+{
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut previous: Option<String> = None;
+    let mut tokens = 0;
+    while tokens < limit {
+        let token = match tokenizer.next()? {
+            Some(token) => token,
+            None => break,
+        };
+        tokens += 1;
+        if let Some(previous) = previous.take() {
+            let lowercase = token.chars().find(|c| c.is_alphanumeric())
+                .is_some_and(char::is_lowercase);
+            let count = counts.entry(previous).or_insert((0, 0));
+            count.0 += lowercase as usize;
+            count.1 += 1;
+        }
+        previous = abbreviation(token, max_len).map(str::to_string);
+    }
+    let mut candidates: Vec<AbbreviationCandidate> = counts.into_iter()
+        .filter(|&(_, (lowercase, _))| lowercase > 0)
+        .map(|(token, (lowercase, total))| AbbreviationCandidate { token, lowercase, total })
+        .collect();
+    // The most often followed by a lower case word first, and of those, the
+    // least often followed by anything else
+    candidates.sort_by(|a, b| {
+        b.lowercase.cmp(&a.lowercase)
+            .then((a.total - a.lowercase).cmp(&(b.total - b.lowercase)))
+            .then(a.token.cmp(&b.token))
+    });
+    Ok(AbbreviationReport { tokens, candidates })
+}
+
+/// How a splitter configuration behaves on a text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Calibration<'a> {
+    /// The number of sentences
+    pub sentences: usize,
+    /// How many sentences each terminator ended
+    pub terminators: Vec<(&'a str, usize)>,
+    /// How many sentences each clause terminator ended
+    pub clause_terminators: Vec<(&'a str, usize)>,
+    /// How many sentences ended by closing a quote
+    pub quotes: usize,
+    /// The indices of sentences that ended without a terminator or a closing
+    /// quote, at the end of a line, a paragraph, a record or the input
+    pub fallbacks: Vec<usize>,
+    /// The indices of sentences longer than the given number of characters
+    pub long: Vec<usize>,
+    /// The indices of sentences split again after a quote in them was given
+    /// up on, as set with `SentenceSplitter::set_max_quote_tokens`
+    pub recovered: Vec<usize>,
+}
+
+/// Splits the rest of the input and reports which rules ended the sentences,
+/// which sentences only ended because the input (or record) did, which were
+/// split again after a quote was given up on, and which sentences are longer
+/// than `max_chars`
+///
+/// ```
+/// use token::analysis::calibrate;
+///
+/// let text = "It works. Does it? Sure... Well, the last one is unfinished";
+/// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+/// let mut splitter = token::SentenceSplitter::new(
+///     tokenizer, vec![".", "!", "?"], vec![]
+/// );
+///
+/// let report = calibrate(&mut splitter, 30).unwrap();
+/// assert_eq!(3, report.sentences);
+/// assert_eq!(vec![(".", 1), ("!", 0), ("?", 1)], report.terminators);
+/// assert_eq!(vec![2], report.fallbacks);
+/// assert_eq!(vec![2], report.long);
+///
+/// let text = "Whereas the parties agree: Now it is agreed; so be it.";
+/// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+/// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+/// splitter.set_clause_terminators(vec![":", ";"]);
+///
+/// let report = calibrate(&mut splitter, 80).unwrap();
+/// assert_eq!(2, report.sentences);
+/// assert_eq!(vec![(".", 1)], report.terminators);
+/// assert_eq!(vec![(":", 1), (";", 0)], report.clause_terminators);
+///
+/// let text = "Fine. He said \"no. Not at all. Never. Ok.";
+/// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+/// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec!["\""]);
+/// splitter.set_max_quote_tokens(Some(3));
+///
+/// let report = calibrate(&mut splitter, 80).unwrap();
+/// assert_eq!(vec![1, 2], report.recovered);
+/// ```
+pub fn calibrate<'a, R: Read>(splitter: &mut SentenceSplitter<'a, R>, max_chars: usize)
+    -> io::Result<Calibration<'a>>
+{
+    let mut report = Calibration {
+        sentences: 0,
+        terminators: splitter.terminators.iter().map(|t| (*t, 0)).collect(),
+        clause_terminators: splitter.clause_terminators.iter().map(|t| (*t, 0)).collect(),
+        quotes: 0,
+        fallbacks: Vec::new(),
+        long: Vec::new(),
+        recovered: Vec::new(),
+    };
+    loop {
+        let (fresh, recoveries) = (splitter.fresh(), splitter.quote_recoveries());
+        let sentence = match splitter.next()? {
+            Some(sentence) => sentence,
+            None => break,
+        };
+        if sentence.chars().count() > max_chars {
+            report.long.push(report.sentences);
+        }
+        if !fresh || splitter.quote_recoveries() > recoveries {
+            report.recovered.push(report.sentences);
+        }
+        match splitter.last_break() {
+            Break::Terminator(t) => {
+                let mut counts = report.terminators.iter_mut().chain(&mut report.clause_terminators);
+                if let Some(count) = counts.find(|c| c.0 == t) {
+                    count.1 += 1;
+                }
+            }
+            Break::Quote(_) => report.quotes += 1,
+            Break::Newline | Break::Paragraph | Break::Record | Break::End => {
+                report.fallbacks.push(report.sentences)
+            }
+        }
+        report.sentences += 1;
+    }
+    Ok(report)
+}
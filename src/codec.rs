@@ -0,0 +1,257 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Decoders of tokens and sentences for `tokio_util::codec`, so that a
+//! `FramedRead` over a socket gives a stream of them. This module needs the
+//! `codec` feature.
+//!
+//! The codecs are given a function that creates a tokenizer or splitter for
+//! a slice of the input, so that they can be configured as usual. Whenever
+//! more input arrives, the input that hasn't been returned yet is tokenized
+//! again, and everything but the last token or sentence is returned, since
+//! the last one may go on in the input to come. It is returned once the
+//! input ends. As whether a sentence ends can depend on the whole of the
+//! token after it, such a sentence is also held back while the first token
+//! of the next one may go on.
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use tokio_util::codec::Decoder;
+//! use token::codec::SentenceCodec;
+//!
+//! let mut codec = SentenceCodec::new(|input| {
+//!     let tokenizer = token::Tokenizer::new(input, vec![' ', '\n']);
+//!     token::SentenceSplitter::new(tokenizer, vec![".", "?"], vec![])
+//! });
+//! let mut input = BytesMut::from("Hello there. How ar");
+//! assert_eq!(Some("Hello there.".to_string()), codec.decode(&mut input).unwrap());
+//! assert_eq!(None, codec.decode(&mut input).unwrap());
+//! input.extend_from_slice(b"e you? Fi");
+//! assert_eq!(Some("How are you?".to_string()), codec.decode(&mut input).unwrap());
+//! assert_eq!(None, codec.decode(&mut input).unwrap());
+//! input.extend_from_slice(b"ne.");
+//! assert_eq!(Some("Fine.".to_string()), codec.decode_eof(&mut input).unwrap());
+//! assert_eq!(None, codec.decode_eof(&mut input).unwrap());
+//! ```
+
+use std::collections::VecDeque;
+use std::io;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::Decoder;
+
+use crate::decode::{sequence, Sequence};
+use crate::{Limits, SentenceSplitter, Tokenizer};
+
+/// Returns how much of the input is whole chars, leaving out a char that
+/// has only partly arrived
+fn complete(input: &[u8]) -> usize {
+    for start in (input.len().saturating_sub(3)..input.len()).rev() {
+        if input[start] & 0xC0 != 0x80 {
+            if let Sequence::Incomplete = sequence(&input[start..]) {
+                return start;
+            }
+            break;
+        }
+    }
+    input.len()
+}
+
+/// A decoder of the tokens of the input
+///
+/// ```
+/// use bytes::BytesMut;
+/// use tokio_util::codec::Decoder;
+/// use token::codec::TokenCodec;
+///
+/// let mut codec = TokenCodec::new(|input| token::Tokenizer::new(input, vec![' ']));
+/// let mut input = BytesMut::from("GET /ind");
+/// assert_eq!(Some("GET".to_string()), codec.decode(&mut input).unwrap());
+/// assert_eq!(None, codec.decode(&mut input).unwrap());
+/// input.extend_from_slice(b"ex.html HTTP/1.1");
+/// assert_eq!(Some("/index.html".to_string()), codec.decode(&mut input).unwrap());
+/// assert_eq!(Some("HTTP/1.1".to_string()), codec.decode_eof(&mut input).unwrap());
+/// ```
+pub struct TokenCodec<F> {
+    make: F,
+    ready: VecDeque<String>,
+    limits: Limits,
+    /// How many bytes of the input have been used
+    read: u64,
+}
+
+impl<F> TokenCodec<F> where F: for<'b> Fn(&'b [u8]) -> Tokenizer<&'b [u8]> {
+    /// Creates a new codec, tokenizing with tokenizers made by `make`
+    pub fn new(make: F) -> TokenCodec<F> {
+        TokenCodec { make, ready: VecDeque::new(), limits: Limits::default(), read: 0 }
+    }
+
+    /// Sets limits on how much input is decoded and how much of it is held
+    /// back until more arrives, as with `Tokenizer::set_limits`. The limits
+    /// on a single call don't apply.
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    /// use token::{LimitExceeded, Limits};
+    /// use token::codec::TokenCodec;
+    ///
+    /// let mut codec = TokenCodec::new(|input| token::Tokenizer::new(input, vec![' ']));
+    /// codec.set_limits(Limits { max_buffered: Some(8), .. Limits::default() });
+    /// let mut input = BytesMut::from("GET /a");
+    /// assert_eq!(Some("GET".to_string()), codec.decode(&mut input).unwrap());
+    /// input.extend_from_slice(b"verylongpath");
+    /// let e = codec.decode(&mut input).unwrap_err();
+    /// let limit = e.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+    /// assert_eq!(Some(&LimitExceeded::Buffered(8)), limit);
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Tokenizes the input and keeps the tokens that are known to be whole
+    fn fill(&mut self, src: &mut BytesMut, eof: bool) -> io::Result<()> {
+        self.limits.check_input(self.read + src.len() as u64)?;
+        let len = if eof { src.len() } else { complete(src) };
+        let mut tokenizer = (self.make)(&src[..len]);
+        let mut last = None;
+        let mut used = 0;
+        while let Some((token, span)) = tokenizer.next_with_span()? {
+            if let Some((token, end)) = last.replace((token.to_string(), span.byte_end)) {
+                self.ready.push_back(token);
+                used = end;
+            }
+        }
+        if eof {
+            self.ready.extend(last.map(|(token, _)| token));
+            used = src.len();
+        }
+        src.advance(used);
+        self.read += used as u64;
+        self.limits.check_buffered(src.len())
+    }
+}
+
+impl<F> Decoder for TokenCodec<F> where F: for<'b> Fn(&'b [u8]) -> Tokenizer<&'b [u8]> {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<String>> {
+        if self.ready.is_empty() {
+            self.fill(src, false)?;
+        }
+        Ok(self.ready.pop_front())
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<String>> {
+        if self.ready.is_empty() {
+            self.fill(src, true)?;
+        }
+        Ok(self.ready.pop_front())
+    }
+}
+
+/// A decoder of the sentences of the input
+///
+/// ```
+/// use bytes::BytesMut;
+/// use tokio_util::codec::Decoder;
+/// use token::codec::SentenceCodec;
+///
+/// let mut codec = SentenceCodec::new(|input| {
+///     token::SentenceSplitter::german(token::Tokenizer::new(input, vec![' ']))
+/// });
+/// let mut input = BytesMut::from("Wir treffen uns am 3. Ju");
+/// assert_eq!(None, codec.decode(&mut input).unwrap());
+/// input.extend_from_slice(b"li im Park. Gut.");
+/// assert_eq!(Some("Wir treffen uns am 3. Juli im Park.".to_string()),
+///            codec.decode(&mut input).unwrap());
+/// assert_eq!(Some("Gut.".to_string()), codec.decode_eof(&mut input).unwrap());
+///
+/// // A quote that runs past its limit at the end of the input so far may
+/// // still close once the rest arrives
+/// let mut codec = SentenceCodec::new(|input| {
+///     let tokenizer = token::Tokenizer::new(input, vec![' ']);
+///     let mut splitter = token::SentenceSplitter::new(tokenizer, vec![".", "?"], vec!["\""]);
+///     splitter.set_max_quote_tokens(Some(3));
+///     splitter
+/// });
+/// let mut input = BytesMut::from("\"a b. c d");
+/// assert_eq!(None, codec.decode(&mut input).unwrap());
+/// input.extend_from_slice(b".\" e.");
+/// assert_eq!(Some("\"a b. c d.\"".to_string()), codec.decode(&mut input).unwrap());
+/// assert_eq!(Some("e.".to_string()), codec.decode_eof(&mut input).unwrap());
+/// ```
+pub struct SentenceCodec<F> {
+    make: F,
+    ready: VecDeque<String>,
+    limits: Limits,
+    /// How many bytes of the input have been used
+    read: u64,
+}
+
+impl<'a, F> SentenceCodec<F> where F: for<'b> Fn(&'b [u8]) -> SentenceSplitter<'a, &'b [u8]> {
+    /// Creates a new codec, splitting with splitters made by `make`
+    pub fn new(make: F) -> SentenceCodec<F> {
+        SentenceCodec { make, ready: VecDeque::new(), limits: Limits::default(), read: 0 }
+    }
+
+    /// Sets limits on how much input is decoded and how much of it is held
+    /// back until more arrives, as with `TokenCodec::set_limits`
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Splits the input and keeps the sentences that are known to be whole
+    fn fill(&mut self, src: &mut BytesMut, eof: bool) -> io::Result<()> {
+        self.limits.check_input(self.read + src.len() as u64)?;
+        let len = if eof { src.len() } else { complete(src) };
+        let mut splitter = (self.make)(&src[..len]);
+        let mut sentences = Vec::new();
+        while let Some((sentence, span)) = splitter.next_with_span()? {
+            let sentence = sentence.to_string();
+            sentences.push((sentence, span.byte_end, splitter.first_token_end(), splitter.looked_ahead()));
+        }
+        let recovered = splitter.recovered().filter(|&(_, end)| end >= len);
+        if let Some((start, _)) = recovered.filter(|_| !eof) {
+            // A quote was given up on at a token that may go on, and may
+            // still close it
+            while sentences.last().is_some_and(|&(_, end, _, _)| end > start) {
+                sentences.pop();
+            }
+        } else if !eof {
+            // The last sentence may go on, and if its first token may too,
+            // the sentence before it may not have ended where it did
+            if let Some((_, _, first, _)) = sentences.pop() {
+                if first >= len && sentences.last().is_some_and(|&(_, _, _, looked_ahead)| looked_ahead) {
+                    sentences.pop();
+                }
+            }
+        }
+        let used = if eof { src.len() } else { sentences.last().map_or(0, |&(_, end, _, _)| end) };
+        self.ready.extend(sentences.into_iter().map(|(sentence, _, _, _)| sentence));
+        src.advance(used);
+        self.read += used as u64;
+        self.limits.check_buffered(src.len())
+    }
+}
+
+impl<'a, F> Decoder for SentenceCodec<F>
+    where F: for<'b> Fn(&'b [u8]) -> SentenceSplitter<'a, &'b [u8]>
+{
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<String>> {
+        if self.ready.is_empty() {
+            self.fill(src, false)?;
+        }
+        Ok(self.ready.pop_front())
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<String>> {
+        if self.ready.is_empty() {
+            self.fill(src, true)?;
+        }
+        Ok(self.ready.pop_front())
+    }
+}
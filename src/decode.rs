@@ -0,0 +1,210 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Incremental decoding of UTF-8 from buffered readers
+
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::io::{BufRead, Seek, SeekFrom};
+
+#[cfg(feature = "std")]
+use crate::InvalidUtf8;
+
+/// How the bytes at the start of a buffer look as UTF-8
+pub(crate) enum Sequence {
+    /// A complete, valid sequence of the given length
+    Valid(usize),
+    /// A valid prefix of a sequence that needs more bytes
+    Incomplete,
+    /// The first byte cannot start a valid sequence here
+    Invalid,
+}
+
+pub(crate) fn sequence(bytes: &[u8]) -> Sequence {
+    let (len, low, high) = match bytes[0] {
+        0x00..=0x7F => return Sequence::Valid(1),
+        0xC2..=0xDF => (2, 0x80, 0xBF),
+        0xE0 => (3, 0xA0, 0xBF),
+        0xE1..=0xEC | 0xEE..=0xEF => (3, 0x80, 0xBF),
+        0xED => (3, 0x80, 0x9F), // No surrogates
+        0xF0 => (4, 0x90, 0xBF),
+        0xF1..=0xF3 => (4, 0x80, 0xBF),
+        0xF4 => (4, 0x80, 0x8F), // Nothing above U+10FFFF
+        _ => return Sequence::Invalid,
+    };
+    for (i, &b) in bytes.iter().enumerate().take(len).skip(1) {
+        let (low, high) = if i == 1 { (low, high) } else { (0x80, 0xBF) };
+        if b < low || b > high {
+            return Sequence::Invalid;
+        }
+    }
+    if bytes.len() < len {
+        Sequence::Incomplete
+    } else {
+        Sequence::Valid(len)
+    }
+}
+
+/// Decodes a sequence already known to be valid
+pub(crate) fn decode(bytes: &[u8]) -> char {
+    let code = match bytes.len() {
+        1 => u32::from(bytes[0]),
+        2 => u32::from(bytes[0] & 0x1F) << 6 | u32::from(bytes[1] & 0x3F),
+        3 => {
+            u32::from(bytes[0] & 0x0F) << 12 | u32::from(bytes[1] & 0x3F) << 6
+                | u32::from(bytes[2] & 0x3F)
+        }
+        _ => {
+            u32::from(bytes[0] & 0x07) << 18 | u32::from(bytes[1] & 0x3F) << 12
+                | u32::from(bytes[2] & 0x3F) << 6 | u32::from(bytes[3] & 0x3F)
+        }
+    };
+    // `sequence` rules out surrogates and overlong or too large values
+    char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// What the last bytes looked at amounted to
+#[cfg(feature = "std")]
+enum Step {
+    Char(char),
+    More,
+    Invalid,
+}
+
+/// An iterator over the chars of a buffered reader, decoded as UTF-8
+///
+/// Sequences are decoded straight from the buffer of the reader, so only a
+/// sequence split between two fills is copied. Invalid input is handled as
+/// given by the policy, where an invalid sequence is the longest prefix of a
+/// valid one (or a single byte), as with `String::from_utf8_lossy`.
+#[cfg(feature = "std")]
+pub(crate) struct Chars<B: BufRead> {
+    reader: B,
+    policy: InvalidUtf8,
+    partial: [u8; 4],
+    pending: usize,
+    offset: u64,
+}
+
+#[cfg(feature = "std")]
+impl<B: BufRead> Chars<B> {
+    pub(crate) fn new(reader: B, policy: InvalidUtf8) -> Chars<B> {
+        Chars { reader, policy, partial: [0; 4], pending: 0, offset: 0 }
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: InvalidUtf8) {
+        self.policy = policy;
+    }
+
+    /// Returns how many bytes of the input have been decoded
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Sets the offset of the reader in the input, for a reader that doesn't
+    /// start at its beginning
+    pub(crate) fn set_offset(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    /// Returns the buffer of the reader, filling it if it is empty, for the
+    /// caller to read bytes from directly. It is empty in the middle of a
+    /// sequence, and at the end of the input.
+    pub(crate) fn buffer(&mut self) -> io::Result<&[u8]> {
+        if self.pending > 0 {
+            return Ok(&[]);
+        }
+        match self.reader.fill_buf() {
+            Ok(buf) => Ok(buf),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => Ok(&[]),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Marks bytes returned by `buffer` as read
+    pub(crate) fn consume(&mut self, len: usize) {
+        self.reader.consume(len);
+        self.offset += len as u64;
+    }
+
+    fn next_char(&mut self) -> io::Result<Option<char>> {
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let (used, step) = if self.pending == 0 {
+                match buf.first() {
+                    None => return Ok(None),
+                    Some(&b) if b < 0x80 => (1, Step::Char(b as char)),
+                    Some(&b) => match sequence(buf) {
+                        Sequence::Valid(len) => (len, Step::Char(decode(&buf[..len]))),
+                        // Look at the rest of the sequence a byte at a time
+                        _ => {
+                            self.partial[0] = b;
+                            self.pending = 1;
+                            (1, Step::More)
+                        }
+                    },
+                }
+            } else if buf.is_empty() {
+                // The input ended in the middle of a sequence
+                (0, Step::Invalid)
+            } else {
+                self.partial[self.pending] = buf[0];
+                match sequence(&self.partial[..self.pending + 1]) {
+                    Sequence::Valid(len) => (1, Step::Char(decode(&self.partial[..len]))),
+                    Sequence::Incomplete => {
+                        self.pending += 1;
+                        (1, Step::More)
+                    }
+                    // The byte may start the next sequence, so leave it
+                    Sequence::Invalid => (0, Step::Invalid),
+                }
+            };
+            self.reader.consume(used);
+            self.offset += used as u64;
+            match step {
+                Step::Char(c) => {
+                    self.pending = 0;
+                    return Ok(Some(c));
+                }
+                Step::More => {}
+                Step::Invalid => {
+                    let start = self.offset - self.pending as u64;
+                    self.pending = 0;
+                    match self.policy {
+                        InvalidUtf8::Error => {
+                            let message = format!("invalid UTF-8 at byte {}", start);
+                            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                        }
+                        InvalidUtf8::Replace => return Ok(Some(char::REPLACEMENT_CHARACTER)),
+                        InvalidUtf8::Skip => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: BufRead + Seek> Chars<B> {
+    /// Moves to a byte offset of the input, dropping any sequence that was
+    /// started
+    pub(crate) fn seek(&mut self, offset: u64) -> io::Result<()> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.pending = 0;
+        self.offset = offset;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: BufRead> Iterator for Chars<B> {
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<io::Result<char>> {
+        self.next_char().transpose()
+    }
+}
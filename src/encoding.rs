@@ -0,0 +1,548 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Readers that transcode other encodings into UTF-8, so that their output
+//! can be fed directly into a `Tokenizer`, and a writer that transcodes
+//! UTF-8 into UTF-16 for the programs that expect it
+//!
+//! ```
+//! use token::encoding::Utf16Reader;
+//!
+//! // "Hi 😀" in little-endian UTF-16, with a byte order mark
+//! let source: &[u8] = b"\xff\xfeH\x00i\x00 \x00\x3d\xd8\x00\xde";
+//!
+//! let mut tokenizer = token::Tokenizer::new(Utf16Reader::new(source), vec![' ']);
+//! assert_eq!(Some("Hi"), tokenizer.next().unwrap());
+//! assert_eq!(Some("😀"), tokenizer.next().unwrap());
+//! assert_eq!(None,       tokenizer.next().unwrap());
+//! ```
+
+use std::io;
+use std::io::{Read, Write};
+
+use crate::decode::{sequence, Sequence};
+
+/// The order of the two bytes in a UTF-16 code unit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The least significant byte first
+    LittleEndian,
+    /// The most significant byte first
+    BigEndian,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A reader decoding UTF-16 from the inner reader and returning it as UTF-8
+pub struct Utf16Reader<R: Read> {
+    reader: R,
+    order: ByteOrder,
+    detect: bool,
+    start: bool,
+    input: Vec<u8>,
+    output: String,
+    pos: usize,
+}
+
+impl <R> Utf16Reader<R> where R: Read {
+    /// Creates a new reader that picks the byte order from the byte order
+    /// mark at the start of the input, or uses little-endian (as written by
+    /// most Windows tools) when there is none
+    pub fn new(reader: R) -> Utf16Reader<R> {
+        Utf16Reader {
+            reader,
+            order: ByteOrder::LittleEndian,
+            detect: true,
+            start: true,
+            input: Vec::new(),
+            output: String::new(),
+            pos: 0,
+        }
+    }
+
+    /// Creates a new reader with a fixed byte order. A byte order mark at the
+    /// start of the input is still skipped if it matches the given order.
+    ///
+    /// ```
+    /// use token::encoding::{ByteOrder, Utf16Reader};
+    /// use std::io::Read;
+    ///
+    /// let source: &[u8] = b"\x00a\x00b";
+    /// let mut text = String::new();
+    /// Utf16Reader::with_byte_order(source, ByteOrder::BigEndian)
+    ///     .read_to_string(&mut text).unwrap();
+    /// assert_eq!("ab", text);
+    /// ```
+    pub fn with_byte_order(reader: R, order: ByteOrder) -> Utf16Reader<R> {
+        let mut utf16 = Utf16Reader::new(reader);
+        utf16.order = order;
+        utf16.detect = false;
+        utf16
+    }
+
+    /// Returns the byte order used for decoding. Until the first read this
+    /// may not yet reflect the byte order mark of the input.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.order
+    }
+
+    fn unit(&self, a: u8, b: u8) -> u32 {
+        match self.order {
+            ByteOrder::LittleEndian => (a as u32) | ((b as u32) << 8),
+            ByteOrder::BigEndian    => ((a as u32) << 8) | (b as u32),
+        }
+    }
+
+    /// Reads more input and decodes as many whole characters as possible.
+    /// Returns false when the inner reader is exhausted.
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 4096];
+        let read = self.reader.read(&mut chunk)?;
+        if read == 0 {
+            if !self.input.is_empty() {
+                return Err(invalid("truncated UTF-16 input"));
+            }
+            return Ok(false);
+        }
+        self.input.extend(chunk[..read].iter().cloned());
+
+        let mut i = 0;
+        if self.start {
+            if self.input.len() < 2 {
+                return Ok(true);
+            }
+            match (self.detect, self.input[0], self.input[1]) {
+                (true, 0xFF, 0xFE) => { self.order = ByteOrder::LittleEndian; i = 2; }
+                (true, 0xFE, 0xFF) => { self.order = ByteOrder::BigEndian; i = 2; }
+                (false, a, b) if self.unit(a, b) == 0xFEFF => { i = 2; }
+                _ => {}
+            }
+            self.start = false;
+        }
+
+        while i + 2 <= self.input.len() {
+            let unit = self.unit(self.input[i], self.input[i + 1]);
+            let code = if (0xD800..0xDC00).contains(&unit) {
+                if i + 4 > self.input.len() {
+                    break; // Wait for the low surrogate
+                }
+                let low = self.unit(self.input[i + 2], self.input[i + 3]);
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(invalid("unpaired surrogate in UTF-16 input"));
+                }
+                i += 4;
+                0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+            } else if (0xDC00..=0xDFFF).contains(&unit) {
+                return Err(invalid("unpaired surrogate in UTF-16 input"));
+            } else {
+                i += 2;
+                unit
+            };
+            // Surrogates are handled above, so this is always a valid char
+            self.output.push(std::char::from_u32(code).unwrap());
+        }
+        self.input.drain(..i);
+        Ok(true)
+    }
+}
+
+impl <R> Read for Utf16Reader<R> where R: Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            self.output.clear();
+            self.pos = 0;
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+        let available = &self.output.as_bytes()[self.pos..];
+        let count = std::cmp::min(available.len(), buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.pos += count;
+        Ok(count)
+    }
+}
+
+/// A writer encoding the UTF-8 written to it as UTF-16, starting with a
+/// byte order mark. A char may be split over several writes.
+///
+/// ```
+/// use token::encoding::Utf16Writer;
+/// use std::io::Write;
+///
+/// let mut writer = Utf16Writer::new(Vec::new());
+/// writer.write_all("Hi \u{1F600}".as_bytes()).unwrap();
+/// assert_eq!(b"\xff\xfeH\x00i\x00 \x00\x3d\xd8\x00\xde", &writer.get_ref()[..]);
+/// ```
+pub struct Utf16Writer<W: Write> {
+    writer: W,
+    order: ByteOrder,
+    pending: Vec<u8>,
+}
+
+impl <W> Utf16Writer<W> where W: Write {
+    /// Creates a new writer writing little-endian UTF-16, as most Windows
+    /// tools expect
+    pub fn new(writer: W) -> Utf16Writer<W> {
+        Utf16Writer::with_byte_order(writer, ByteOrder::LittleEndian)
+    }
+
+    /// Creates a new writer writing UTF-16 in the given byte order
+    ///
+    /// ```
+    /// use token::encoding::{ByteOrder, Utf16Writer};
+    /// use std::io::Write;
+    ///
+    /// let mut writer = Utf16Writer::with_byte_order(Vec::new(), ByteOrder::BigEndian);
+    /// write!(writer, "ab").unwrap();
+    /// assert_eq!(b"\xfe\xff\x00a\x00b", &writer.get_ref()[..]);
+    /// ```
+    pub fn with_byte_order(writer: W, order: ByteOrder) -> Utf16Writer<W> {
+        Utf16Writer {
+            writer,
+            order,
+            // The byte order mark goes out with the first write
+            pending: "\u{FEFF}".as_bytes().to_vec(),
+        }
+    }
+
+    /// Returns the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl <W> Write for Utf16Writer<W> where W: Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let before = self.pending.len();
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            // The rest is the start of a char that the next write finishes
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => {
+                self.pending.truncate(before);
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                    "invalid UTF-8 written to a UTF-16 writer"));
+            }
+        };
+        let mut output = Vec::with_capacity(valid * 2);
+        // Checked just above
+        for unit in std::str::from_utf8(&self.pending[..valid]).unwrap().encode_utf16() {
+            match self.order {
+                ByteOrder::LittleEndian => output.extend_from_slice(&unit.to_le_bytes()),
+                ByteOrder::BigEndian => output.extend_from_slice(&unit.to_be_bytes()),
+            }
+        }
+        self.writer.write_all(&output)?;
+        self.pending.drain(..valid);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The characters for the bytes 0x80 to 0x9F in Windows-1252. The five
+/// unassigned bytes are mapped to the matching C1 control characters.
+static WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Decodes a single byte as Windows-1252
+pub fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WINDOWS_1252[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// A permissive UTF-8 reader, that passes valid UTF-8 through unchanged and
+/// decodes every byte that is not part of a valid sequence as Windows-1252,
+/// which is by far the most common source of mixed-up text
+///
+/// ```
+/// use token::encoding::PermissiveReader;
+///
+/// // "café" where the 'é' was saved as Windows-1252
+/// let source: &[u8] = b"un caf\xe9 noir";
+/// let mut reader = PermissiveReader::new(source);
+/// {
+///     let mut tokenizer = token::Tokenizer::new(&mut reader, vec![' ']);
+///     assert_eq!(Some("un"),   tokenizer.next().unwrap());
+///     assert_eq!(Some("café"), tokenizer.next().unwrap());
+///     assert_eq!(Some("noir"), tokenizer.next().unwrap());
+/// }
+/// assert_eq!(1, reader.fallbacks());
+/// assert_eq!(Some(6), reader.first_fallback());
+/// ```
+pub struct PermissiveReader<R: Read> {
+    reader: R,
+    input: Vec<u8>,
+    output: String,
+    pos: usize,
+    offset: u64,
+    eof: bool,
+    fallbacks: u64,
+    first_fallback: Option<u64>,
+}
+
+impl <R> PermissiveReader<R> where R: Read {
+    /// Creates a new permissive reader
+    pub fn new(reader: R) -> PermissiveReader<R> {
+        PermissiveReader {
+            reader,
+            input: Vec::new(),
+            output: String::new(),
+            pos: 0,
+            offset: 0,
+            eof: false,
+            fallbacks: 0,
+            first_fallback: None,
+        }
+    }
+
+    /// Returns how many bytes have been decoded as Windows-1252 so far
+    pub fn fallbacks(&self) -> u64 {
+        self.fallbacks
+    }
+
+    /// Returns the offset in the input of the first byte that was decoded as
+    /// Windows-1252, if any
+    pub fn first_fallback(&self) -> Option<u64> {
+        self.first_fallback
+    }
+
+    /// Reads more input and decodes it. Returns false when all input has
+    /// been decoded.
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let mut chunk = [0; 4096];
+        let read = self.reader.read(&mut chunk)?;
+        if read == 0 {
+            self.eof = true;
+        }
+        self.input.extend(chunk[..read].iter().cloned());
+
+        let mut i = 0;
+        while i < self.input.len() {
+            match sequence(&self.input[i..]) {
+                Sequence::Valid(len) => {
+                    // Valid by construction
+                    let s = std::str::from_utf8(&self.input[i..i + len]).unwrap();
+                    self.output.push_str(s);
+                    i += len;
+                }
+                Sequence::Incomplete if !self.eof => break,
+                _ => {
+                    self.output.push(windows_1252(self.input[i]));
+                    if self.first_fallback.is_none() {
+                        self.first_fallback = Some(self.offset + i as u64);
+                    }
+                    self.fallbacks += 1;
+                    i += 1;
+                }
+            }
+        }
+        self.input.drain(..i);
+        self.offset += i as u64;
+        Ok(true)
+    }
+}
+
+impl <R> Read for PermissiveReader<R> where R: Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            self.output.clear();
+            self.pos = 0;
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+        let available = &self.output.as_bytes()[self.pos..];
+        let count = std::cmp::min(available.len(), buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.pos += count;
+        Ok(count)
+    }
+}
+
+/// An encoding the crate can decode
+#[cfg(feature = "detect")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, which includes ASCII
+    Utf8,
+    /// UTF-16 in the given byte order
+    Utf16(ByteOrder),
+    /// The Windows code page for Western European languages, a superset of
+    /// Latin-1
+    Windows1252,
+}
+
+/// The result of sniffing the encoding of a sample
+#[cfg(feature = "detect")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Detection {
+    /// The most likely encoding
+    pub encoding: Encoding,
+    /// How sure the detector is, from 0 to 1
+    pub confidence: f32,
+    /// The length of the byte order mark at the start of the sample, if any
+    pub bom: usize,
+}
+
+/// Guesses the encoding of the start of a text
+///
+/// ```
+/// use token::encoding::{detect, Encoding};
+///
+/// assert_eq!(Encoding::Utf8, detect("søvnig".as_bytes()).encoding);
+/// assert_eq!(Encoding::Windows1252, detect(b"s\xf8vnig").encoding);
+/// ```
+#[cfg(feature = "detect")]
+pub fn detect(sample: &[u8]) -> Detection {
+    let found = |encoding, confidence, bom| {
+        Detection { encoding, confidence, bom }
+    };
+
+    // Byte order marks are as certain as it gets
+    if sample.starts_with(b"\xEF\xBB\xBF") {
+        return found(Encoding::Utf8, 1.0, 3);
+    } else if sample.starts_with(b"\xFF\xFE") {
+        return found(Encoding::Utf16(ByteOrder::LittleEndian), 1.0, 2);
+    } else if sample.starts_with(b"\xFE\xFF") {
+        return found(Encoding::Utf16(ByteOrder::BigEndian), 1.0, 2);
+    }
+
+    // Mostly-ASCII UTF-16 has a zero in every other byte
+    let pairs = sample.len() / 2;
+    if pairs > 0 {
+        let even = sample.chunks(2).filter(|p| p.len() == 2 && p[0] == 0).count();
+        let odd = sample.chunks(2).filter(|p| p.len() == 2 && p[1] == 0).count();
+        let (zeros, other, order) = if odd > even {
+            (odd, even, ByteOrder::LittleEndian)
+        } else {
+            (even, odd, ByteOrder::BigEndian)
+        };
+        if zeros * 10 > pairs * 3 && other * 20 < pairs {
+            return found(Encoding::Utf16(order), zeros as f32 / pairs as f32, 0);
+        }
+    }
+
+    // Otherwise it's either UTF-8 or (mostly) Windows-1252
+    let mut i = 0;
+    let mut multibyte = 0;
+    let mut invalid = 0;
+    while i < sample.len() {
+        match sequence(&sample[i..]) {
+            Sequence::Valid(1) => i += 1,
+            Sequence::Valid(len) => { multibyte += len; i += len; }
+            Sequence::Incomplete => break, // Cut off by the end of the sample
+            Sequence::Invalid => { invalid += 1; i += 1; }
+        }
+    }
+    if invalid == 0 {
+        let confidence = if multibyte > 0 { 1.0 } else { 0.5 };
+        found(Encoding::Utf8, confidence, 0)
+    } else {
+        found(Encoding::Windows1252, invalid as f32 / (invalid + multibyte) as f32, 0)
+    }
+}
+
+#[cfg(feature = "detect")]
+enum Decoder<R: Read> {
+    Utf8(R),
+    Utf16(Utf16Reader<R>),
+    Windows1252(PermissiveReader<R>),
+}
+
+/// A reader that sniffs the encoding of the first part of its input and
+/// decodes all of it to UTF-8 accordingly
+///
+/// Text detected as Windows-1252 is decoded with a `PermissiveReader`, so any
+/// valid UTF-8 later in the input is still kept.
+///
+/// ```
+/// use token::encoding::{AutoReader, ByteOrder, Encoding};
+///
+/// let source: &[u8] = b"H\x00i\x00 \x00t\x00h\x00e\x00r\x00e\x00";
+/// let reader = AutoReader::new(source).unwrap();
+/// assert_eq!(Encoding::Utf16(ByteOrder::LittleEndian), reader.detection().encoding);
+///
+/// let mut tokenizer = token::Tokenizer::new(reader, vec![' ']);
+/// assert_eq!(Some("Hi"),    tokenizer.next().unwrap());
+/// assert_eq!(Some("there"), tokenizer.next().unwrap());
+/// ```
+#[cfg(feature = "detect")]
+pub struct AutoReader<R: Read> {
+    decoder: Decoder<io::Chain<io::Cursor<Vec<u8>>, R>>,
+    detection: Detection,
+}
+
+#[cfg(feature = "detect")]
+impl <R> AutoReader<R> where R: Read {
+    /// Creates a new reader, sniffing the encoding from the first 64 KB
+    pub fn new(reader: R) -> io::Result<AutoReader<R>> {
+        AutoReader::with_sample_size(reader, 64 * 1024)
+    }
+
+    /// Creates a new reader, sniffing the encoding from the given number of
+    /// bytes at the start of the input (at least enough for a byte order mark)
+    pub fn with_sample_size(mut reader: R, size: usize) -> io::Result<AutoReader<R>> {
+        let size = std::cmp::max(size, 4);
+        let mut sample = vec![0; size];
+        let mut filled = 0;
+        while filled < size {
+            match reader.read(&mut sample[filled..])? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        sample.truncate(filled);
+
+        let detection = detect(&sample);
+        let mut cursor = io::Cursor::new(sample);
+        let decoder = match detection.encoding {
+            Encoding::Utf8 => {
+                cursor.set_position(detection.bom as u64);
+                Decoder::Utf8(cursor.chain(reader))
+            }
+            Encoding::Utf16(order) => {
+                let chained = cursor.chain(reader);
+                if detection.bom > 0 {
+                    Decoder::Utf16(Utf16Reader::new(chained))
+                } else {
+                    Decoder::Utf16(Utf16Reader::with_byte_order(chained, order))
+                }
+            }
+            Encoding::Windows1252 => {
+                Decoder::Windows1252(PermissiveReader::new(cursor.chain(reader)))
+            }
+        };
+        Ok(AutoReader { decoder, detection })
+    }
+
+    /// Returns the detected encoding and how confident the detection was
+    pub fn detection(&self) -> Detection {
+        self.detection
+    }
+}
+
+#[cfg(feature = "detect")]
+impl <R> Read for AutoReader<R> where R: Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.decoder {
+            Decoder::Utf8(ref mut reader) => reader.read(buf),
+            Decoder::Utf16(ref mut reader) => reader.read(buf),
+            Decoder::Windows1252(ref mut reader) => reader.read(buf),
+        }
+    }
+}
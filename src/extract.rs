@@ -0,0 +1,71 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Reading the text of spans back from the input, for when a large input is
+//! first scanned for the spans of its tokens or sentences, and only some of
+//! them are needed as text later
+//!
+//! ```
+//! use std::io::Cursor;
+//! use token::extract::Extractor;
+//!
+//! let text = "First sentence. Second one. Third.";
+//!
+//! // Scan the input once, keeping only the spans
+//! let tokenizer = token::Tokenizer::new(Cursor::new(text), vec![' ']);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+//! let mut spans = Vec::new();
+//! while let Some(span) = splitter.next_span().unwrap() {
+//!     spans.push(span);
+//! }
+//!
+//! // Then read back only the sentences that are needed
+//! let mut extractor = Extractor::new(Cursor::new(text));
+//! assert_eq!("Third.", extractor.extract(spans[2]).unwrap());
+//! assert_eq!("Second one.", extractor.extract(spans[1]).unwrap());
+//! ```
+
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::str;
+
+use crate::Span;
+
+/// Reads the text of spans from a seekable reader
+///
+/// The spans must come from a tokenizer or sentence splitter that read the
+/// same bytes from the start. A span of a sentence covers the separators
+/// between its tokens as they were in the input, so its text is not always
+/// the same as the sentence returned by the splitter.
+pub struct Extractor<R: Read + Seek> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read + Seek> Extractor<R> {
+    /// Creates a new extractor of a reader
+    pub fn new(reader: R) -> Extractor<R> {
+        Extractor { reader, buffer: Vec::new() }
+    }
+
+    /// Returns the bytes of the input that the span covers
+    pub fn extract_bytes(&mut self, span: Span) -> io::Result<&[u8]> {
+        let len = span.byte_end.saturating_sub(span.byte_start);
+        self.buffer.resize(len, 0);
+        self.reader.seek(SeekFrom::Start(span.byte_start as u64))?;
+        self.reader.read_exact(&mut self.buffer)?;
+        Ok(&self.buffer)
+    }
+
+    /// Returns the text that the span covers. This fails with an
+    /// `io::Error` of the kind `InvalidData` if the text isn't valid UTF-8,
+    /// as when invalid input was replaced by the tokenizer.
+    pub fn extract(&mut self, span: Span) -> io::Result<&str> {
+        let bytes = self.extract_bytes(span)?;
+        str::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Returns the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
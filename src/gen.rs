@@ -0,0 +1,217 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Generation of random, but reproducible, text with known tokens and
+//! sentences, for testing tokenizer and splitter configurations against
+//!
+//! ```
+//! let separators = vec![' ', '\n', '\t'];
+//! let terminators = vec![".", "!", "?"];
+//! let quotes = vec!["\""];
+//!
+//! let mut generator = token::gen::Generator::new(
+//!     42, separators.clone(), terminators.clone(), quotes.clone()
+//! );
+//! let sample = generator.generate(20);
+//!
+//! let tokenizer = token::Tokenizer::new(sample.text.as_bytes(), separators);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, terminators, quotes);
+//! for expected in &sample.sentences {
+//!     assert_eq!(Some(&expected[..]), splitter.next().unwrap());
+//! }
+//! assert_eq!(None, splitter.next().unwrap());
+//! ```
+
+/// A small, seedable pseudo-random number generator (xorshift64*)
+///
+/// It is not suitable for anything security related, but produces the same
+/// numbers for the same seed on every platform.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a new generator from a seed
+    pub fn new(seed: u64) -> Rng {
+        // The state must never be zero
+        Rng { state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    /// Returns the next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a random number in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns a random number in `0..1`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A generated text with the tokens and sentences it should be split into
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    /// The text itself
+    pub text: String,
+    /// The tokens a `Tokenizer` with the same separators should return
+    pub tokens: Vec<String>,
+    /// The sentences a `SentenceSplitter` with the same configuration should
+    /// return
+    pub sentences: Vec<String>,
+    /// The indices in `tokens` of the abbreviations, after which no sentence
+    /// ends even though they end in a terminator
+    pub non_boundaries: Vec<usize>,
+}
+
+/// A generator of random text for a given splitter configuration
+///
+/// Words are made from `letters` (lowercase ASCII and a few accented
+/// letters by default), so separators, terminators and quotes should not be
+/// letters. Terminators ending in `..` continue sentences in the splitter, so
+/// they are never used to end generated sentences.
+pub struct Generator<'a> {
+    rng: Rng,
+    separators: Vec<char>,
+    terminators: Vec<&'a str>,
+    quotes: Vec<&'a str>,
+    /// The letters words are made of
+    pub letters: Vec<char>,
+    /// The maximum number of words in a sentence
+    pub max_words: usize,
+    /// The chance that a sentence ends in a quotation
+    pub quote_chance: f64,
+    /// The abbreviations to put inside sentences, which should be those of
+    /// the splitter, and not contain separators or quotes
+    pub abbreviations: Vec<&'a str>,
+    /// The chance that a sentence has an abbreviation in it
+    pub abbreviation_chance: f64,
+}
+
+impl <'a> Generator<'a> {
+    /// Creates a new generator with the configuration to generate text for
+    pub fn new(seed: u64, separators: Vec<char>, terminators: Vec<&'a str>,
+               quotes: Vec<&'a str>) -> Generator<'a>
+    {
+        Generator {
+            rng: Rng::new(seed),
+            separators,
+            terminators: terminators.into_iter()
+                .filter(|t| !t.is_empty() && !t.ends_with(".."))
+                .collect(),
+            quotes: quotes.into_iter().filter(|q| !q.is_empty()).collect(),
+            letters: "abcdefghijklmnopqrstuvwxyzæøåéü".chars().collect(),
+            max_words: 12,
+            quote_chance: 0.2,
+            abbreviations: Vec::new(),
+            abbreviation_chance: 0.3,
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let len = 1 + self.rng.below(8);
+        (0..len).map(|_| self.letters[self.rng.below(self.letters.len())]).collect()
+    }
+
+    fn gap(&mut self, text: &mut String) {
+        if self.separators.is_empty() {
+            return;
+        }
+        for _ in 0..1 + self.rng.below(3) {
+            text.push(self.separators[self.rng.below(self.separators.len())]);
+        }
+    }
+
+    /// Generates a text with the given number of sentences
+    ///
+    /// Without any separators all sentences become a single token, and
+    /// without terminators or quotes the whole text is one sentence.
+    ///
+    /// ```
+    /// let separators = vec![' '];
+    /// let abbreviations = vec!["dr.", "etc."];
+    ///
+    /// let mut generator = token::gen::Generator::new(7, separators.clone(), vec!["."], vec![]);
+    /// generator.abbreviations = abbreviations.clone();
+    /// generator.abbreviation_chance = 1.0;
+    /// let sample = generator.generate(10);
+    /// assert!(!sample.non_boundaries.is_empty());
+    ///
+    /// let tokenizer = token::Tokenizer::new(sample.text.as_bytes(), separators);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_abbreviations(abbreviations);
+    /// for expected in &sample.sentences {
+    ///     assert_eq!(Some(&expected[..]), splitter.next().unwrap());
+    /// }
+    /// ```
+    pub fn generate(&mut self, sentences: usize) -> Sample {
+        let mut sample = Sample {
+            text: String::new(),
+            tokens: Vec::new(),
+            sentences: Vec::new(),
+            non_boundaries: Vec::new(),
+        };
+        let mut words = Vec::new();
+        for _ in 0..sentences {
+            // Build the words of the sentence, then decorate the ending
+            let count = 1 + self.rng.below(self.max_words);
+            words.clear();
+            for _ in 0..count {
+                words.push(self.word());
+            }
+            // An abbreviation anywhere but at the end, where it would end
+            // the sentence as far as the text goes
+            let mut abbreviation = None;
+            if !self.abbreviations.is_empty() && count > 1 && self.rng.next_f64() < self.abbreviation_chance {
+                let at = self.rng.below(count - 1);
+                words[at] = self.abbreviations[self.rng.below(self.abbreviations.len())].to_string();
+                abbreviation = Some(sample.tokens.len() + at);
+            }
+            let quoted = !self.quotes.is_empty() && (self.terminators.is_empty()
+                || self.rng.next_f64() < self.quote_chance);
+            if quoted {
+                let quote = self.quotes[self.rng.below(self.quotes.len())];
+                let start = self.rng.below(count);
+                words[start] = format!("{}{}", quote, words[start]);
+                words[count - 1].push_str(quote);
+            } else if !self.terminators.is_empty() {
+                let terminator = self.terminators[self.rng.below(self.terminators.len())];
+                words[count - 1].push_str(terminator);
+            }
+
+            for word in &words {
+                if self.rng.below(4) == 0 || !sample.text.is_empty() {
+                    self.gap(&mut sample.text);
+                }
+                sample.text.push_str(word);
+                sample.tokens.push(word.clone());
+            }
+            sample.sentences.push(words.join(" "));
+            sample.non_boundaries.extend(abbreviation);
+        }
+        if !sample.text.is_empty() && self.rng.below(4) == 0 {
+            self.gap(&mut sample.text);
+        }
+        self.fix_degenerate(&mut sample);
+        sample
+    }
+
+    /// Fixes the expected output for configurations where tokens or
+    /// sentences cannot be told apart
+    fn fix_degenerate(&self, sample: &mut Sample) {
+        if self.separators.is_empty() && !sample.text.is_empty() {
+            sample.tokens = vec![sample.text.clone()];
+            sample.sentences = vec![sample.text.clone()];
+        } else if self.terminators.is_empty() && self.quotes.is_empty()
+                  && !sample.sentences.is_empty() {
+            sample.sentences = vec![sample.tokens.join(" ")];
+        }
+    }
+}
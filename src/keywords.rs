@@ -0,0 +1,208 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Keyword extraction from the sentences of a document, with RAKE and
+//! TextRank
+//!
+//! Both methods work on candidate phrases: runs of words not broken up by
+//! stopwords or punctuation. Words are compared in lower case, and the
+//! phrases returned are lowercased too. The crate has no stopword lists of
+//! its own, so they must be given by the caller.
+//!
+//! ```
+//! use token::keywords::rake;
+//!
+//! let text = "Compatibility of systems of linear constraints over the set of \
+//!             natural numbers. Criteria of compatibility of a system of linear \
+//!             Diophantine equations are considered.";
+//! let stopwords = ["of", "over", "the", "a", "are", "set"];
+//!
+//! let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+//! let mut sentences = Vec::new();
+//! while let Some(sentence) = splitter.next().unwrap() {
+//!     sentences.push(sentence.to_string());
+//! }
+//!
+//! let keywords = rake(&sentences, &stopwords);
+//! assert_eq!("linear diophantine equations", keywords[0].phrase);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use crate::split_word;
+
+/// A phrase and how important it is to the document
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyword {
+    /// The lowercased words of the phrase, separated by single spaces
+    pub phrase: String,
+    /// The score of the phrase, only comparable with other scores from the
+    /// same method and document
+    pub score: f64,
+}
+
+/// Splits sentences into their candidate phrases
+fn candidates<I>(sentences: I, stopwords: &[&str]) -> Vec<Vec<Vec<String>>>
+    where I: IntoIterator, I::Item: AsRef<str>
+{
+    fn end(phrases: &mut Vec<Vec<String>>, phrase: &mut Vec<String>) {
+        if !phrase.is_empty() {
+            phrases.push(mem::take(phrase));
+        }
+    }
+
+    let stopwords: HashSet<String> = stopwords.iter().map(|w| w.to_lowercase()).collect();
+    let mut sentence_phrases = Vec::new();
+    for sentence in sentences {
+        let mut phrases = Vec::new();
+        let mut phrase = Vec::new();
+        for token in sentence.as_ref().split_whitespace() {
+            let (before, word, after) = split_word(token);
+            if !before.is_empty() {
+                end(&mut phrases, &mut phrase);
+            }
+            let word = word.to_lowercase();
+            if word.is_empty() || stopwords.contains(&word) {
+                end(&mut phrases, &mut phrase);
+            } else {
+                phrase.push(word);
+            }
+            if !after.is_empty() {
+                end(&mut phrases, &mut phrase);
+            }
+        }
+        end(&mut phrases, &mut phrase);
+        sentence_phrases.push(phrases);
+    }
+    sentence_phrases
+}
+
+/// Sorts phrases by score, highest first
+fn ranked(scores: HashMap<String, f64>) -> Vec<Keyword> {
+    let mut keywords: Vec<_> = scores.into_iter()
+        .map(|(phrase, score)| Keyword { phrase, score })
+        .collect();
+    keywords.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
+            .then_with(|| a.phrase.cmp(&b.phrase))
+    });
+    keywords
+}
+
+/// Ranks the candidate phrases of a document with RAKE (Rapid Automatic
+/// Keyword Extraction)
+///
+/// Every word is scored by how many words it appears in phrases with
+/// (itself included), divided by how often it appears, and a phrase is
+/// scored by the sum of the scores of its words. This favours long phrases
+/// of words that are seldom seen alone.
+pub fn rake<I>(sentences: I, stopwords: &[&str]) -> Vec<Keyword>
+    where I: IntoIterator, I::Item: AsRef<str>
+{
+    let sentences = candidates(sentences, stopwords);
+    let mut frequency = HashMap::new();
+    let mut degree = HashMap::new();
+    for phrase in sentences.iter().flatten() {
+        for word in phrase {
+            *frequency.entry(&word[..]).or_insert(0) += 1;
+            *degree.entry(&word[..]).or_insert(0) += phrase.len();
+        }
+    }
+    let mut scores = HashMap::new();
+    for phrase in sentences.iter().flatten() {
+        let score = phrase.iter().fold(0.0, |sum, word| {
+            sum + degree[&word[..]] as f64 / frequency[&word[..]] as f64
+        });
+        scores.insert(phrase.join(" "), score);
+    }
+    ranked(scores)
+}
+
+/// The damping factor of TextRank, as in PageRank
+const DAMPING: f64 = 0.85;
+
+/// Ranks the candidate phrases of a document with TextRank
+///
+/// The words are nodes in a graph, with edges between words seen at most
+/// `window - 1` words apart in the same sentence, not counting stopwords
+/// and punctuation. The words are ranked by PageRank over this graph, and
+/// the phrases returned are runs of the best third of the words in the
+/// candidate phrases, scored by the sum of the ranks of their words.
+///
+/// ```
+/// use token::keywords::textrank;
+///
+/// let sentences = ["The borrow checker enforces the borrow rules",
+///                  "Borrow rules keep references valid",
+///                  "The compiler checks borrow rules before running code"];
+/// let stopwords = ["the", "before"];
+///
+/// let keywords = textrank(&sentences, &stopwords, 2);
+/// assert_eq!("borrow rules", keywords[0].phrase);
+/// ```
+pub fn textrank<I>(sentences: I, stopwords: &[&str], window: usize) -> Vec<Keyword>
+    where I: IntoIterator, I::Item: AsRef<str>
+{
+    let sentences = candidates(sentences, stopwords);
+
+    // Build the co-occurrence graph
+    let mut index = HashMap::new();
+    let mut words = Vec::new();
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+    for phrases in &sentences {
+        let ids: Vec<usize> = phrases.iter().flatten().map(|word| {
+            *index.entry(&word[..]).or_insert_with(|| {
+                words.push(&word[..]);
+                edges.push(Vec::new());
+                words.len() - 1
+            })
+        }).collect();
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in ids.iter().skip(i + 1).take(window.saturating_sub(1)) {
+                if a != b && !edges[a].contains(&b) {
+                    edges[a].push(b);
+                    edges[b].push(a);
+                }
+            }
+        }
+    }
+
+    // Rank the words until the ranks settle
+    let mut ranks = vec![1.0; words.len()];
+    for _ in 0..100 {
+        let mut next = vec![1.0 - DAMPING; words.len()];
+        for (a, neighbours) in edges.iter().enumerate() {
+            let share = DAMPING * ranks[a] / neighbours.len() as f64;
+            for &b in neighbours {
+                next[b] += share;
+            }
+        }
+        let change = ranks.iter().zip(&next).fold(0.0, |max: f64, (r, n)| max.max((r - n).abs()));
+        ranks = next;
+        if change < 1e-6 {
+            break;
+        }
+    }
+
+    let mut best: Vec<usize> = (0..words.len()).collect();
+    best.sort_by(|&a, &b| {
+        ranks[b].partial_cmp(&ranks[a]).unwrap_or(Ordering::Equal)
+            .then_with(|| words[a].cmp(words[b]))
+    });
+    best.truncate(words.len().div_ceil(3));
+    let best: HashSet<usize> = best.into_iter().collect();
+
+    // Join the best words into phrases where they follow each other
+    let mut scores = HashMap::new();
+    for phrase in sentences.iter().flatten() {
+        for run in phrase.split(|word| !best.contains(&index[&word[..]])) {
+            if !run.is_empty() {
+                let score = run.iter().fold(0.0, |sum, word| sum + ranks[index[&word[..]]]);
+                scores.insert(run.join(" "), score);
+            }
+        }
+    }
+    ranked(scores)
+}
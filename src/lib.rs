@@ -9,6 +9,7 @@
 //! This is what to expect when parsing a string (or input from a reader)
 //!
 //! ```
+//! # #[cfg(feature = "std")] {
 //! let separators = vec![' ', '\n', '\t', '\r'];
 //! let source: &str = "    Hello world \n  How do you do\t-Finely I hope";
 //!
@@ -23,22 +24,545 @@
 //! assert_eq!(Some("I"),       tokenizer.next().unwrap());
 //! assert_eq!(Some("hope"),    tokenizer.next().unwrap());
 //! assert_eq!(None,            tokenizer.next().unwrap());
+//! # }
 //! ```
 
-#![feature(io)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use std::vec::Vec;
-use std::iter::Iterator;
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use std::io;
-use std::io::Read;
+#[cfg(feature = "std")]
+use std::io::{BufReader, Read, Seek};
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::time::Instant;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cmp;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::mem;
+use core::str;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use crate::decode::{decode, sequence, Chars, Sequence};
+#[cfg(feature = "std")]
+use crate::overlap::Normalization;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "codec")]
+pub mod codec;
+mod decode;
+#[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod extract;
+#[cfg(feature = "std")]
+pub mod gen;
+#[cfg(feature = "std")]
+pub mod keywords;
+#[cfg(feature = "std")]
+pub mod lookup;
+#[cfg(feature = "std")]
+pub mod overlap;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(feature = "regex")]
+pub mod pattern;
+#[cfg(feature = "std")]
+pub mod preview;
+#[cfg(feature = "std")]
+pub mod push;
+#[cfg(feature = "std")]
+pub mod recognize;
+#[cfg(feature = "std")]
+pub mod resegment;
+#[cfg(feature = "std")]
+pub mod shell;
+#[cfg(feature = "std")]
+pub mod skipgrams;
+pub mod source;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "futures")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod summary;
+#[cfg(feature = "std")]
+pub mod truecase;
+
+/// A configuration that would make a tokenizer or splitter misbehave
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// There are no separators, so the whole input becomes one token
+    NoSeparators,
+    /// An empty terminator, which would end every sentence after one token
+    EmptyTerminator,
+    /// An empty quote, which would match every token
+    EmptyQuote,
+    /// A terminator containing a separator, which no token can ever end with
+    SeparatorInTerminator(String, char),
+    /// A quote containing a separator, which no token can ever start with
+    SeparatorInQuote(String, char),
+    /// A quote that is also a terminator, which makes it ambiguous whether a
+    /// token ending in it closes a quote or ends a sentence
+    QuoteIsTerminator(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::NoSeparators => write!(f, "no separators were given"),
+            ConfigError::EmptyTerminator => write!(f, "a terminator is empty"),
+            ConfigError::EmptyQuote => write!(f, "a quote is empty"),
+            ConfigError::SeparatorInTerminator(ref t, c) => {
+                write!(f, "the terminator {:?} contains the separator {:?}", t, c)
+            }
+            ConfigError::SeparatorInQuote(ref q, c) => {
+                write!(f, "the quote {:?} contains the separator {:?}", q, c)
+            }
+            ConfigError::QuoteIsTerminator(ref q) => {
+                write!(f, "{:?} is both a quote and a terminator", q)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ConfigError {}
+
+/// Limits on the resources used for an input, for when it can't be trusted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Limits {
+    /// The most bytes to read from the input in total, counting invalid
+    /// UTF-8 that was skipped or replaced as the bytes it was
+    pub max_input: Option<u64>,
+    /// The most bytes to hold in a token, gap or sentence buffer
+    pub max_buffered: Option<usize>,
+    /// The most bytes to read in a single call to `next`
+    pub max_call_input: Option<u64>,
+    /// The longest time to spend in a single call to `next`. This is checked
+    /// between characters, so it cannot interrupt a read that blocks.
+    pub max_call_time: Option<Duration>,
+}
+
+#[cfg(feature = "std")]
+impl Limits {
+    fn check_input(&self, read: u64) -> io::Result<()> {
+        match self.max_input {
+            Some(max) if read > max => Err(limit_error(LimitExceeded::Input(max))),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_call(&self, read: u64, started: Option<Instant>) -> io::Result<()> {
+        if let Some(max) = self.max_call_input {
+            if read > max {
+                return Err(limit_error(LimitExceeded::CallInput(max)));
+            }
+        }
+        if let (Some(max), Some(started)) = (self.max_call_time, started) {
+            if started.elapsed() > max {
+                return Err(limit_error(LimitExceeded::CallTime(max)));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_buffered(&self, buffered: usize) -> io::Result<()> {
+        match self.max_buffered {
+            Some(max) if buffered > max => Err(limit_error(LimitExceeded::Buffered(max))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The error returned (wrapped in an `io::Error`) when a limit is exceeded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitExceeded {
+    /// More than this many bytes of input were read
+    Input(u64),
+    /// More than this many bytes would have been buffered
+    Buffered(usize),
+    /// More than this many bytes were read in a single call
+    CallInput(u64),
+    /// A single call took longer than this
+    CallTime(Duration),
+    /// A token was longer than this many bytes, and the policy for long
+    /// tokens is `LongTokens::Error`
+    TokenLength(usize),
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LimitExceeded::Input(max) => write!(f, "read more than {} bytes of input", max),
+            LimitExceeded::Buffered(max) => write!(f, "buffered more than {} bytes", max),
+            LimitExceeded::CallInput(max) => {
+                write!(f, "read more than {} bytes in a single call", max)
+            }
+            LimitExceeded::CallTime(max) => {
+                write!(f, "spent more than {:?} in a single call", max)
+            }
+            LimitExceeded::TokenLength(max) => write!(f, "token longer than {} bytes", max),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for LimitExceeded {}
+
+/// What to do with input that is not valid UTF-8
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvalidUtf8 {
+    /// Fail with an `io::Error` of the kind `InvalidData`
+    #[default]
+    Error,
+    /// Replace each invalid sequence with U+FFFD REPLACEMENT CHARACTER
+    Replace,
+    /// Leave invalid sequences out
+    Skip,
+}
+
+/// What to do with tokens longer than the maximum token length
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LongTokens {
+    /// Fail with an `io::Error` wrapping `LimitExceeded::TokenLength`
+    #[default]
+    Error,
+    /// Keep the start of the token, and leave out the rest of it
+    Truncate,
+    /// Return the token in pieces of at most the maximum length, with
+    /// empty gaps between them
+    SplitAtLimit,
+}
+
+/// What to do with a quote that is still open at the end of the input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnclosedQuote {
+    /// Fail with an `io::Error` of the kind `InvalidData`
+    #[default]
+    Error,
+    /// Return the rest of the input, from the start of the token with the
+    /// quote, as the last token
+    Remainder,
+}
+
+/// The maximum token length of a tokenizer, and what to do about tokens
+/// that are longer
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+struct TokenLength {
+    max: Option<usize>,
+    policy: LongTokens,
+    /// Whether the rest of the current token is being left out
+    truncating: bool,
+}
+
+#[cfg(feature = "std")]
+impl TokenLength {
+    /// Returns how many of `len` more bytes fit in a token of `token_len`
+    /// bytes, or fails if the policy is to fail
+    fn room(&mut self, token_len: usize, len: usize) -> io::Result<usize> {
+        if self.truncating {
+            return Ok(0);
+        }
+        match self.max {
+            Some(max) if token_len + len > max => {
+                match self.policy {
+                    LongTokens::Error => return Err(limit_error(LimitExceeded::TokenLength(max))),
+                    LongTokens::Truncate => self.truncating = true,
+                    LongTokens::SplitAtLimit => {}
+                }
+                Ok(max - token_len)
+            }
+            _ => Ok(len),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn limit_error(error: LimitExceeded) -> io::Error {
+    io::Error::other(error)
+}
+
+/// How a tokenizer decides whether a char is a separator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeparatorStrategy {
+    /// All separators are ASCII, so a bitmap answers for every char, and
+    /// text in memory is scanned a byte at a time
+    Bitmap,
+    /// ASCII chars are looked up in a bitmap, and other chars are compared
+    /// with each of the non-ASCII separators
+    BitmapAndScan,
+    /// ASCII chars are looked up in a bitmap, and other chars are checked
+    /// with `char::is_whitespace` before being compared with any non-ASCII
+    /// separators
+    BitmapAndWhitespace,
+    /// Chars are looked up as with one of the other strategies, and then
+    /// passed to a `SeparatorMatcher`
+    Matcher,
+}
+
+/// Decides which chars separate tokens, for tokenizers created with
+/// `with_matcher`. It is implemented for closures taking a `char`.
+pub trait SeparatorMatcher {
+    /// Returns whether `c` separates tokens
+    fn is_separator(&self, c: char) -> bool;
+}
+
+impl<F: Fn(char) -> bool> SeparatorMatcher for F {
+    fn is_separator(&self, c: char) -> bool {
+        self(c)
+    }
+}
+
+/// A piece of the input, as returned by `Tokenizer::next_piece`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Piece<'a> {
+    /// A token
+    Content(&'a str),
+    /// The separators between two tokens, or at either end of the input
+    Gap(&'a str),
+}
+
+/// Where a token or sentence is in the input
+///
+/// The offsets are of bytes of the input, before any invalid UTF-8 in it was
+/// replaced or skipped. Lines and columns count from 1, with columns
+/// counted in chars.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// The offset of the first byte
+    pub byte_start: usize,
+    /// The offset just past the last byte
+    pub byte_end: usize,
+    /// The line of the first char
+    pub line: usize,
+    /// The column of the first char
+    pub column: usize,
+}
+
+/// A set of separators with constant-time lookup of ASCII chars
+struct SeparatorSet {
+    ascii: [u64; 2],
+    other: Vec<char>,
+    whitespace: bool,
+    matcher: Option<Box<dyn SeparatorMatcher + Send>>,
+}
+
+impl SeparatorSet {
+    fn new(separators: &[char]) -> SeparatorSet {
+        let mut set = SeparatorSet {
+            ascii: [0; 2],
+            other: Vec::new(),
+            whitespace: false,
+            matcher: None,
+        };
+        for &c in separators {
+            set.insert(c);
+        }
+        set
+    }
+
+    fn insert(&mut self, c: char) {
+        let code = c as u32;
+        if code < 128 {
+            self.ascii[(code >> 6) as usize] |= 1 << (code & 63);
+        } else if !self.other.contains(&c) {
+            self.other.push(c);
+        }
+    }
+
+    /// Adds everything `char::is_whitespace` accepts
+    fn with_unicode_whitespace(mut self) -> SeparatorSet {
+        for c in (0..128u8).map(char::from).filter(|c| c.is_whitespace()) {
+            self.insert(c);
+        }
+        self.whitespace = true;
+        self
+    }
+
+    #[cfg(feature = "std")]
+    fn is_empty(&self) -> bool {
+        self.ascii == [0; 2] && self.other.is_empty() && !self.whitespace && self.matcher.is_none()
+    }
+
+    fn strategy(&self) -> SeparatorStrategy {
+        if self.matcher.is_some() {
+            SeparatorStrategy::Matcher
+        } else if self.whitespace {
+            SeparatorStrategy::BitmapAndWhitespace
+        } else if self.other.is_empty() {
+            SeparatorStrategy::Bitmap
+        } else {
+            SeparatorStrategy::BitmapAndScan
+        }
+    }
+
+    /// Returns a table of which bytes are separators, if all of them are
+    /// ASCII, so that UTF-8 can be scanned a byte at a time without
+    /// decoding it
+    fn byte_table(&self) -> Option<[bool; 256]> {
+        if self.strategy() != SeparatorStrategy::Bitmap {
+            return None;
+        }
+        let mut table = [false; 256];
+        for (b, separator) in (0..128u8).zip(table.iter_mut()) {
+            *separator = self.contains(char::from(b));
+        }
+        Some(table)
+    }
+
+    #[inline]
+    fn contains(&self, c: char) -> bool {
+        let code = c as u32;
+        let found = if code < 128 {
+            self.ascii[(code >> 6) as usize] & (1 << (code & 63)) != 0
+        } else {
+            (self.whitespace && c.is_whitespace()) || self.other.contains(&c)
+        };
+        found || self.matcher.as_ref().is_some_and(|m| m.is_separator(c))
+    }
+}
+
+/// Splits a token into the punctuation before the word, the word itself and
+/// the punctuation after it
+#[cfg(feature = "std")]
+fn split_word(token: &str) -> (&str, &str, &str) {
+    let start = token.find(|c: char| c.is_alphanumeric()).unwrap_or(token.len());
+    let end = match token.rfind(|c: char| c.is_alphanumeric()) {
+        Some(i) => i + token[i..].chars().next().map_or(0, |c| c.len_utf8()),
+        None => start,
+    };
+    (&token[..start], &token[start..end], &token[end..])
+}
+
+/// Roman numerals and their values, from the largest
+#[cfg(feature = "std")]
+const ROMAN_NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"), (100, "C"), (90, "XC"),
+    (50, "L"), (40, "XL"), (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+];
+
+/// Returns whether the word is an upper case Roman numeral written the
+/// usual way, so that `"IV"` is one but `"IIII"` is not
+#[cfg(feature = "std")]
+fn is_roman_numeral(word: &str) -> bool {
+    let mut rest = word;
+    let mut value = 0;
+    for &(v, numeral) in &ROMAN_NUMERALS {
+        while let Some(after) = rest.strip_prefix(numeral) {
+            value += v;
+            rest = after;
+        }
+    }
+    if !rest.is_empty() || value == 0 {
+        return false;
+    }
+    let mut usual = String::new();
+    for &(v, numeral) in &ROMAN_NUMERALS {
+        while value >= v {
+            usual.push_str(numeral);
+            value -= v;
+        }
+    }
+    usual == word
+}
+
+/// A token that was read ahead by `Tokenizer::peek_n`, with what is needed
+/// to return it later as if it was read then
+#[cfg(feature = "std")]
+struct Peeked {
+    token: String,
+    gap: String,
+    span: Span,
+    record: usize,
+    /// Whether there was a token, or the input ended
+    found: bool,
+}
+
+/// Where a tokenizer was in its input, returned by `Tokenizer::checkpoint`,
+/// so that a long job can be stopped and later go on where it was. With the
+/// `serde` feature, it can be serialized.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    byte_offset: u64,
+    line: usize,
+    column: usize,
+    newlines: usize,
+    record: usize,
+    /// The start of the gap before the next token, which was read already
+    gap: String,
+}
+
+#[cfg(feature = "std")]
+impl Checkpoint {
+    /// Returns the offset of the input to go on reading from
+    pub fn byte_offset(&self) -> u64 {
+        self.byte_offset
+    }
+}
 
 /// A tokenizer returning string slices from a reader
+#[cfg(feature = "std")]
 pub struct Tokenizer<R: Read> {
-    separators: Vec<char>,
-    chars: io::Chars<R>,
+    lookup: SeparatorSet,
+    chars: Chars<BufReader<R>>,
+    invalid_utf8: InvalidUtf8,
     current: String,
+    gap: String,
+    ended_on: String,
+    strings: Vec<Vec<char>>,
+    string_starts: SeparatorSet,
+    stops: [bool; 256],
+    lookahead: VecDeque<(char, usize)>,
+    at: Span,
+    span: Span,
+    line: usize,
+    column: usize,
+    limits: Limits,
+    length: TokenLength,
+    carried: Option<char>,
+    peeked: VecDeque<Peeked>,
+    unreadable: bool,
+    read: u64,
+    call_read: u64,
+    call_started: Option<Instant>,
+    ticks: u32,
+    records: bool,
+    newlines: usize,
+    record: usize,
+    content_pending: bool,
+    quotes: Vec<char>,
+    unclosed_quotes: UnclosedQuote,
+    /// The quote that was opened in the token being read, and not closed
+    open_quote: Option<char>,
+    escape: Option<char>,
+    /// Whether the last char was an escape inside a quote
+    escaped: bool,
 }
 
+#[cfg(feature = "std")]
 impl <R> Tokenizer<R> where R: Read {
     /// Creates a new tokenizer from a reader and a set of separating characters
     ///
@@ -50,135 +574,2674 @@ impl <R> Tokenizer<R> where R: Read {
     /// ```
     ///
     pub fn new(reader: R, separators: Vec<char>) -> Tokenizer<R> {
-        Tokenizer {
-            chars: reader.chars(),
-            separators: separators,
+        let mut tokenizer = Tokenizer {
+            chars: Chars::new(BufReader::new(reader), InvalidUtf8::Error),
+            invalid_utf8: InvalidUtf8::Error,
+            lookup: SeparatorSet::new(&separators),
             current: String::new(),
-        }
+            gap: String::new(),
+            ended_on: String::new(),
+            strings: Vec::new(),
+            string_starts: SeparatorSet::new(&[]),
+            stops: [true; 256],
+            lookahead: VecDeque::new(),
+            at: Span::default(),
+            span: Span::default(),
+            line: 1,
+            column: 1,
+            limits: Limits::default(),
+            length: TokenLength::default(),
+            carried: None,
+            peeked: VecDeque::new(),
+            unreadable: false,
+            read: 0,
+            call_read: 0,
+            call_started: None,
+            ticks: 0,
+            records: false,
+            newlines: 0,
+            record: 0,
+            content_pending: false,
+            quotes: Vec::new(),
+            unclosed_quotes: UnclosedQuote::Error,
+            open_quote: None,
+            escape: None,
+            escaped: false,
+        };
+        tokenizer.update_stops();
+        tokenizer
     }
-    
-    /// Returns a string slice of the next non-empty sequence that terminates
-    /// in one of the specified separator strings
-    pub fn next(&mut self) -> Result<Option<&str>, io::CharsError> {
-        self.current.clear();
-        for res in &mut self.chars {
-            let c = try!(res);
-            // Is `c` a separator?
-            if self.separators.iter().any(|t| *t == c) {
-                if !&self.current.is_empty() {
-                    return Ok(Some(&self.current));
+
+    /// Creates a new tokenizer that separates tokens on the chars the matcher
+    /// accepts, such as every char that isn't alphanumeric
+    ///
+    /// ```
+    /// let source: &str = "state-of-the-art, über cool!";
+    ///
+    /// let mut tokenizer = token::Tokenizer::with_matcher(
+    ///     source.as_bytes(), |c: char| !c.is_alphanumeric()
+    /// );
+    /// assert_eq!(Some("state"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("of"),    tokenizer.next().unwrap());
+    /// assert_eq!(Some("the"),   tokenizer.next().unwrap());
+    /// assert_eq!(Some("art"),   tokenizer.next().unwrap());
+    /// assert_eq!(Some("über"),  tokenizer.next().unwrap());
+    /// ```
+    pub fn with_matcher<M>(reader: R, matcher: M) -> Tokenizer<R>
+        where M: SeparatorMatcher + Send + 'static
+    {
+        let mut tokenizer = Tokenizer::new(reader, Vec::new());
+        tokenizer.lookup.matcher = Some(Box::new(matcher));
+        tokenizer.update_stops();
+        tokenizer
+    }
+
+    /// Creates a new tokenizer from a reader and a set of separating strings.
+    /// Where separators overlap, the longest one that matches is used, so
+    /// `"->"` is a single separator even if `"-"` is one too.
+    ///
+    /// ```
+    /// let source: &str = "x->y - z";
+    ///
+    /// let mut tokenizer = token::Tokenizer::with_strings(source.as_bytes(), vec![" ", "-", "->"]);
+    /// assert_eq!(Some("x"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("y"), tokenizer.next().unwrap());
+    /// assert_eq!("->",      tokenizer.gap());
+    /// assert_eq!(Some("z"), tokenizer.next().unwrap());
+    /// assert_eq!(" - ",     tokenizer.gap());
+    /// ```
+    pub fn with_strings(reader: R, separators: Vec<&str>) -> Tokenizer<R> {
+        let mut tokenizer = Tokenizer::new(reader, Vec::new());
+        tokenizer.add_strings(&separators);
+        tokenizer
+    }
+
+    /// Adds string separators, putting those of a single char with the
+    /// other separating chars
+    fn add_strings<S: AsRef<str>>(&mut self, separators: &[S]) {
+        for separator in separators {
+            let chars: Vec<char> = separator.as_ref().chars().collect();
+            match chars.len() {
+                0 => {}
+                1 => self.lookup.insert(chars[0]),
+                _ => {
+                    if !self.strings.contains(&chars) {
+                        self.string_starts.insert(chars[0]);
+                        self.strings.push(chars);
+                    }
                 }
-            } else {
-                // Just add the char
-                self.current.push(c);
             }
         }
-        // Handle leftover chars
-        if !self.current.is_empty() {
-            Ok(Some(&self.current))
-        } else {
-            Ok(None) // No more chars left
-        }
+        // Longest first, so that the first match is the longest one
+        self.strings.sort_by_key(|s| cmp::Reverse(s.len()));
+        self.update_stops();
     }
-}
 
-/// A structure for iteratively splitting stringy things into sentences
-pub struct SentenceSplitter<'a, R: Read> {
-    tokenizer: Tokenizer<R>,
-    terminators: Vec<&'a str>,
-    current: String,
-    quotes: Vec<&'a str>,
-}
+    /// Works out which bytes `read_buffered` has to stop at: separators, the
+    /// first chars of string separators, line breaks and anything that
+    /// isn't ASCII. This must be done whenever the separators change.
+    fn update_stops(&mut self) {
+        for (b, stop) in (0..=255u8).zip(self.stops.iter_mut()) {
+            let c = char::from(b);
+            *stop = !b.is_ascii() || c == '\n' || self.lookup.contains(c) || self.string_starts.contains(c)
+                || self.quotes.contains(&c) || self.escape == Some(c);
+        }
+    }
 
-impl <'a, R: Read> SentenceSplitter<'a, R> {
+    /// Creates a new tokenizer that separates tokens on all Unicode
+    /// whitespace, such as no-break and ideographic spaces, and on zero
+    /// width spaces, which Unicode does not count as whitespace but which
+    /// separate words in some scripts. More separators can be added with a
+    /// `TokenizerBuilder`.
+    ///
+    /// ```
+    /// let source: &str = "50\u{A0}km\u{3000}東京\u{200B}大阪";
+    ///
+    /// let mut tokenizer = token::Tokenizer::unicode_whitespace(source.as_bytes());
+    /// assert_eq!(Some("50"),   tokenizer.next().unwrap());
+    /// assert_eq!(Some("km"),   tokenizer.next().unwrap());
+    /// assert_eq!(Some("東京"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("大阪"), tokenizer.next().unwrap());
+    /// ```
+    pub fn unicode_whitespace(reader: R) -> Tokenizer<R> {
+        TokenizerBuilder::new().unicode_whitespace().build(reader)
+    }
 
-    /// Creates a new sentence-splitting iterator
+    /// Creates a new tokenizer that treats each line of the input as a
+    /// separate record. Newlines always end a token, whether or not they are
+    /// among the separators.
     ///
     /// ```
-    /// let separators  = vec![' ', '\n', '\t', '\r'];
-    /// let terminators = vec![".", "!", "?"];
-    /// let quotes = vec![]; // For when whole lines are quoted
+    /// let source: &str = "one two\nthree";
     ///
-    /// let text = "I walked down the road.\n\"What did he say\", she asked me.\n\"Nothing\", I replied, and continued walking...\nit wasn't any of my business.\nOr was it?";
+    /// let mut tokenizer = token::Tokenizer::records(source.as_bytes(), vec![' ']);
+    /// assert_eq!(Some("one"),   tokenizer.next().unwrap());
+    /// assert_eq!(0, tokenizer.record());
+    /// assert_eq!(Some("two"),   tokenizer.next().unwrap());
+    /// assert_eq!(0, tokenizer.record());
+    /// assert_eq!(Some("three"), tokenizer.next().unwrap());
+    /// assert_eq!(1, tokenizer.record());
+    /// ```
+    pub fn records(reader: R, separators: Vec<char>) -> Tokenizer<R> {
+        let mut tokenizer = Tokenizer::new(reader, separators);
+        tokenizer.records = true;
+        tokenizer
+    }
+
+    /// Returns the index of the record (line) the last token was read from.
+    /// This is always 0 unless the tokenizer was created with `records`.
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    /// Checks that the separators make sense
     ///
-    /// let mut tokenizer = token::Tokenizer::new(text.as_bytes(), separators);
-    /// let mut splitter = token::SentenceSplitter::new(
-    ///     tokenizer, terminators, quotes
-    /// );
+    /// ```
+    /// use token::{ConfigError, Tokenizer};
     ///
-    /// assert_eq!(Some("I walked down the road."), splitter.next().unwrap());
-    /// assert_eq!(Some("\"What did he say\", she asked me."),
-    ///            splitter.next().unwrap());
-    /// assert_eq!(Some("\"Nothing\", I replied, and continued walking... \
-    ///                  it wasn't any of my business."),
-    ///            splitter.next().unwrap());
-    /// assert_eq!(Some("Or was it?"), splitter.next().unwrap());
-    /// assert_eq!(None, splitter.next().unwrap());
+    /// let tokenizer = Tokenizer::new("".as_bytes(), vec![]);
+    /// assert_eq!(Err(ConfigError::NoSeparators), tokenizer.validate());
     /// ```
-    pub fn new(source: Tokenizer<R>, terminators: Vec<&'a str>,
-               quotes: Vec<&'a str>) -> SentenceSplitter<'a, R>
-    {
-        SentenceSplitter{
-            tokenizer: source,
-            current: String::new(),
-            terminators: terminators,
-            quotes: quotes,
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.lookup.is_empty() && self.strings.is_empty() {
+            return Err(ConfigError::NoSeparators);
         }
+        Ok(())
     }
 
-    /// Returns the next sentence
-    pub fn next(&mut self) -> Result<Option<&str>, io::CharsError> {
-        self.current.clear();
-        let mut quote = "";
-        loop {
-            let s = match try!(self.tokenizer.next()) {
-                Some(s) => s,
-                None => {
-                    if !self.current.is_empty() {
-                        return Ok(Some(&self.current));
-                    } else {
-                        return Ok(None);
-                    }
-                }
+    /// Sets what to do with input that is not valid UTF-8. By default it is
+    /// an error.
+    ///
+    /// ```
+    /// use token::InvalidUtf8;
+    ///
+    /// let source: &[u8] = b"caf\xe9 au lait";
+    /// let mut tokenizer = token::Tokenizer::new(source, vec![' ']);
+    /// tokenizer.set_invalid_utf8(InvalidUtf8::Replace);
+    /// assert_eq!(Some("caf\u{FFFD}"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("au"),          tokenizer.next().unwrap());
+    /// ```
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.invalid_utf8 = policy;
+        self.chars.set_policy(policy);
+    }
+
+    /// Returns how separators are looked up, which depends on whether any
+    /// of them are outside of ASCII
+    ///
+    /// ```
+    /// use token::{SeparatorStrategy, Tokenizer};
+    ///
+    /// let ascii = Tokenizer::new("".as_bytes(), vec![' ', '\t', ',']);
+    /// assert_eq!(SeparatorStrategy::Bitmap, ascii.strategy());
+    ///
+    /// let mixed = Tokenizer::new("".as_bytes(), vec![' ', '\u{3000}']);
+    /// assert_eq!(SeparatorStrategy::BitmapAndScan, mixed.strategy());
+    /// ```
+    pub fn strategy(&self) -> SeparatorStrategy {
+        self.lookup.strategy()
+    }
+
+    /// Sets limits on how much input is read and how much is buffered.
+    /// Exceeding them makes `next` fail with an `io::Error` wrapping a
+    /// `LimitExceeded`, after which the tokenizer should be reset.
+    ///
+    /// ```
+    /// use token::{LimitExceeded, Limits};
+    ///
+    /// let source: &str = "short averyveryverylongtoken";
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_limits(Limits { max_buffered: Some(10), .. Limits::default() });
+    ///
+    /// assert_eq!(Some("short"), tokenizer.next().unwrap());
+    /// match tokenizer.next() {
+    ///     Err(e) => {
+    ///         let limit = e.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+    ///         assert_eq!(Some(&LimitExceeded::Buffered(10)), limit);
+    ///     }
+    ///     _ => panic!("expected the limit to be exceeded"),
+    /// }
+    ///
+    /// // Input that is skipped still counts as read
+    /// let source = vec![0xFF; 5000];
+    /// let mut tokenizer = token::Tokenizer::new(&source[..], vec![' ']);
+    /// tokenizer.set_invalid_utf8(token::InvalidUtf8::Skip);
+    /// tokenizer.set_limits(Limits { max_input: Some(1000), .. Limits::default() });
+    ///
+    /// let e = tokenizer.next().unwrap_err();
+    /// let limit = e.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+    /// assert_eq!(Some(&LimitExceeded::Input(1000)), limit);
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Sets the most bytes a token may hold, or `None` for no maximum, and
+    /// what to do with longer tokens. Unlike `Limits::max_buffered`, this
+    /// does not count the separators before a token. A maximum below 4 is
+    /// taken as 4, so that every char fits in a token.
+    ///
+    /// ```
+    /// use token::LongTokens;
+    ///
+    /// let source: &str = "short averyveryverylongtoken";
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_max_token_len(Some(8), LongTokens::Truncate);
+    /// assert_eq!(Some("short"),    tokenizer.next().unwrap());
+    /// assert_eq!(Some("averyver"), tokenizer.next().unwrap());
+    /// assert_eq!(None,             tokenizer.next().unwrap());
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_max_token_len(Some(8), LongTokens::SplitAtLimit);
+    /// assert_eq!(Some("short"),    tokenizer.next().unwrap());
+    /// assert_eq!(Some("averyver"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("yverylon"), tokenizer.next().unwrap());
+    /// assert_eq!("",               tokenizer.gap());
+    /// assert_eq!(Some("gtoken"),   tokenizer.next().unwrap());
+    /// ```
+    pub fn set_max_token_len(&mut self, max: Option<usize>, policy: LongTokens) {
+        self.length.max = max.map(|max| max.max(4));
+        self.length.policy = policy;
+    }
+
+    /// Sets the quote chars, between which separators are part of the token,
+    /// and what to do with a quote that is never closed. A quote opens
+    /// anywhere in a token and is closed by the same char, and the quotes are
+    /// kept in the token. There are no quotes by default.
+    ///
+    /// ```
+    /// use token::UnclosedQuote;
+    ///
+    /// let source: &str = "set title \"A Tale of Two Cities\" now";
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_quotes(vec!['"'], UnclosedQuote::Error);
+    /// assert_eq!(Some("set"),                      tokenizer.next().unwrap());
+    /// assert_eq!(Some("title"),                    tokenizer.next().unwrap());
+    /// assert_eq!(Some("\"A Tale of Two Cities\""), tokenizer.next().unwrap());
+    /// assert_eq!(Some("now"),                      tokenizer.next().unwrap());
+    ///
+    /// let source: &str = "say 'hi there";
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_quotes(vec!['"', '\''], UnclosedQuote::Remainder);
+    /// assert_eq!(Some("say"),       tokenizer.next().unwrap());
+    /// assert_eq!(Some("'hi there"), tokenizer.next().unwrap());
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_quotes(vec!['"', '\''], UnclosedQuote::Error);
+    /// assert_eq!(Some("say"), tokenizer.next().unwrap());
+    /// assert!(tokenizer.next().is_err());
+    /// ```
+    pub fn set_quotes(&mut self, quotes: Vec<char>, unclosed: UnclosedQuote) {
+        self.quotes = quotes;
+        self.unclosed_quotes = unclosed;
+        self.update_stops();
+    }
+
+    /// Sets the escape char, usually `\`, or `None` for no escapes, as by
+    /// default. An escaped char is part of the token, even if it is a
+    /// separator or a quote, and the escape is left out. Inside a quote, an
+    /// escape only keeps the char after it from closing the quote, and both
+    /// are kept, as the quoted text is. An escape at the end of the input is
+    /// kept as it is.
+    ///
+    /// ```
+    /// use token::UnclosedQuote;
+    ///
+    /// let source: &str = r#"cp my\ file "a \"quoted\" name""#;
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ']);
+    /// tokenizer.set_quotes(vec!['"'], UnclosedQuote::Error);
+    /// tokenizer.set_escape(Some('\\'));
+    /// assert_eq!(Some("cp"),                      tokenizer.next().unwrap());
+    /// assert_eq!(Some("my file"),                 tokenizer.next().unwrap());
+    /// assert_eq!(Some(r#""a \"quoted\" name""#), tokenizer.next().unwrap());
+    /// assert_eq!(None,                            tokenizer.next().unwrap());
+    /// ```
+    pub fn set_escape(&mut self, escape: Option<char>) {
+        self.escape = escape;
+        self.update_stops();
+    }
+
+    /// Starts over on a new reader, keeping the separators and the allocated
+    /// buffers, so that one tokenizer can be reused for many documents
+    pub fn reset(&mut self, reader: R) {
+        self.chars = Chars::new(BufReader::new(reader), self.invalid_utf8);
+        self.clear();
+    }
+
+    /// Returns where the tokenizer is in its input, after the last token that
+    /// was returned. Tokens that were peeked at are read again on resuming.
+    pub fn checkpoint(&self) -> Checkpoint {
+        if let Some(peeked) = self.peeked.front().filter(|peeked| peeked.found) {
+            return Checkpoint {
+                byte_offset: peeked.span.byte_start as u64,
+                line: peeked.span.line,
+                column: peeked.span.column,
+                newlines: peeked.record,
+                record: self.record,
+                gap: peeked.gap.clone(),
             };
-            self.current.push_str(s);
+        }
+        // The char a long token was split before is read again
+        let (offset, line, column) = match self.carried {
+            Some(_) => (self.at.byte_start, self.at.line, self.at.column),
+            None => (self.at.byte_end, self.line, self.column),
+        };
+        Checkpoint {
+            byte_offset: offset as u64,
+            line,
+            column,
+            newlines: self.newlines,
+            record: self.record,
+            gap: self.ended_on.clone(),
+        }
+    }
 
-            // Inside a quote
-            if !quote.is_empty() {
-                if s.ends_with(quote) {
-                    return Ok(Some(&self.current))
-                } else {
-                    self.current.push_str(" ");
-                    continue;
-                }
-            }
+    /// Starts over on a new reader that starts where the checkpoint was,
+    /// such as the same file opened again and moved to its byte offset. The
+    /// tokens, spans and records then go on as if the tokenizer had never
+    /// stopped.
+    ///
+    /// ```
+    /// let text = "one two\nthree four";
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ', '\n']);
+    /// assert_eq!(Some("one"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("two"), tokenizer.next().unwrap());
+    /// let checkpoint = tokenizer.checkpoint();
+    /// assert_eq!(8, checkpoint.byte_offset());
+    ///
+    /// let rest = &text.as_bytes()[checkpoint.byte_offset() as usize..];
+    /// let mut tokenizer = token::Tokenizer::new(rest, vec![' ', '\n']);
+    /// tokenizer.resume(rest, &checkpoint);
+    /// let (token, span) = tokenizer.next_with_span().unwrap().unwrap();
+    /// assert_eq!("three", token);
+    /// assert_eq!((8, 2, 1), (span.byte_start, span.line, span.column));
+    /// assert_eq!("\n", tokenizer.gap());
+    /// ```
+    pub fn resume(&mut self, reader: R, checkpoint: &Checkpoint) {
+        self.reset(reader);
+        self.chars.set_offset(checkpoint.byte_offset);
+        let offset = checkpoint.byte_offset as usize;
+        self.at = Span { byte_start: offset, byte_end: offset, line: checkpoint.line, column: checkpoint.column };
+        self.line = checkpoint.line;
+        self.column = checkpoint.column;
+        self.newlines = checkpoint.newlines;
+        self.record = checkpoint.record;
+        self.ended_on.push_str(&checkpoint.gap);
+    }
 
-            // Not inside a quote
-            // Check to see if a quote is starting
-            match self.quotes.iter().find(|q| s.starts_with(*q)) {
-                Some(q) => {
-                    if s.ends_with(q) { // It can end again
-                        return Ok(Some(&self.current));
-                    }
-                    quote = q;
-                    self.current.push_str(" ");
-                    continue;
-                }
-                None => {}
-            }
+    /// Forgets everything read from the input so far
+    fn clear(&mut self) {
+        self.current.clear();
+        self.gap.clear();
+        self.ended_on.clear();
+        self.lookahead.clear();
+        self.carried = None;
+        self.peeked.clear();
+        self.unreadable = false;
+        self.at = Span::default();
+        self.span = Span::default();
+        self.line = 1;
+        self.column = 1;
+        self.read = 0;
+        self.newlines = 0;
+        self.record = 0;
+        self.content_pending = false;
+        self.open_quote = None;
+        self.escaped = false;
+    }
 
-            // Check whether the token is ending normally
-            // It ends in a terminating character
-            if s.ends_with("..") {
-                // Continue thought trails
-                self.current.push_str(" ");
-                continue;
-            }
-            if self.terminators.iter().any(|t| s.ends_with(*t)) {
+    /// Starts over on a new reader and appends all of its tokens to `out`
+    ///
+    /// ```
+    /// let mut tokenizer = token::Tokenizer::new("a b".as_bytes(), vec![' ']);
+    /// let mut tokens = Vec::new();
+    /// tokenizer.tokenize_into("Hello world".as_bytes(), &mut tokens).unwrap();
+    /// tokenizer.tokenize_into("Bye now".as_bytes(), &mut tokens).unwrap();
+    /// assert_eq!(vec!["Hello", "world", "Bye", "now"], tokens);
+    /// ```
+    pub fn tokenize_into(&mut self, reader: R, out: &mut Vec<String>)
+        -> io::Result<()>
+    {
+        self.reset(reader);
+        while let Some(token) = self.next()? {
+            out.push(token.to_string());
+        }
+        Ok(())
+    }
+    
+    /// Turns the tokenizer into an iterator of owned tokens, for use with
+    /// `for` loops and iterator adapters. This allocates a `String` for every
+    /// token, so `next` is faster when the tokens are only looked at.
+    ///
+    /// The iterator stops after the first error.
+    ///
+    /// ```
+    /// let tokenizer = token::Tokenizer::new("one two  three".as_bytes(), vec![' ']);
+    /// let long: Vec<String> = tokenizer.owned()
+    ///     .map(|token| token.unwrap())
+    ///     .filter(|token| token.len() > 3)
+    ///     .collect();
+    /// assert_eq!(vec!["three"], long);
+    /// ```
+    pub fn owned(self) -> OwnedTokens<R> {
+        OwnedTokens { tokenizer: self, failed: false }
+    }
+
+    /// Returns the separators between the last token and the one before it
+    /// (or the start of the input). Once the input is exhausted, this is the
+    /// separators after the last token, so the input can be reconstructed
+    /// exactly from the tokens and the gaps before them.
+    ///
+    /// ```
+    /// let source: &str = "  Hello\n\nworld ";
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ', '\n']);
+    /// assert_eq!(Some("Hello"), tokenizer.next().unwrap());
+    /// assert_eq!("  ",          tokenizer.gap());
+    /// assert_eq!(Some("world"), tokenizer.next().unwrap());
+    /// assert_eq!("\n\n",        tokenizer.gap());
+    /// assert_eq!(None,          tokenizer.next().unwrap());
+    /// assert_eq!(" ",           tokenizer.gap());
+    /// ```
+    pub fn gap(&self) -> &str {
+        &self.gap
+    }
+
+    /// Starts the budget for a call, as limited by `Limits::max_call_input`
+    /// and `Limits::max_call_time`
+    fn begin_call(&mut self) {
+        self.call_read = self.read;
+        self.call_started = self.limits.max_call_time.map(|_| Instant::now());
+    }
+
+    /// Returns a string slice of the next non-empty sequence that terminates
+    /// in one of the specified separator strings
+    #[allow(clippy::should_implement_trait)] // The token borrows the tokenizer
+    pub fn next(&mut self) -> io::Result<Option<&str>> {
+        self.begin_call();
+        self.content_pending = false;
+        self.next_token()
+    }
+
+    /// Returns the next token together with where it is in the input
+    ///
+    /// ```
+    /// use token::Span;
+    ///
+    /// let source: &str = "fn main() {\n    println!();\n}";
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ', '\n']);
+    /// tokenizer.next().unwrap();
+    /// tokenizer.next().unwrap();
+    /// tokenizer.next().unwrap();
+    /// let (token, span) = tokenizer.next_with_span().unwrap().unwrap();
+    /// assert_eq!("println!();", token);
+    /// assert_eq!(Span { byte_start: 16, byte_end: 27, line: 2, column: 5 }, span);
+    /// assert_eq!(token, &source[span.byte_start..span.byte_end]);
+    /// ```
+    pub fn next_with_span(&mut self) -> io::Result<Option<(&str, Span)>> {
+        if self.next()?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some((&self.current, self.span)))
+    }
+
+    /// Returns where the next token is in the input, for when only the
+    /// boundaries of the tokens are needed. The token is still read into the
+    /// buffer of the tokenizer, which is reused, and its text can be read
+    /// back later with an `extract::Extractor`.
+    pub fn next_span(&mut self) -> io::Result<Option<Span>> {
+        Ok(self.next_with_span()?.map(|(_, span)| span))
+    }
+
+    /// Returns the next token without consuming it, so that the following
+    /// call to `next` returns it too
+    ///
+    /// ```
+    /// let mut tokenizer = token::Tokenizer::new("Mr. Smith".as_bytes(), vec![' ']);
+    /// assert_eq!(Some("Mr."),   tokenizer.next().unwrap());
+    /// assert_eq!(Some("Smith"), tokenizer.peek().unwrap());
+    /// assert_eq!(Some("Smith"), tokenizer.next().unwrap());
+    /// assert_eq!(None,          tokenizer.peek().unwrap());
+    /// ```
+    pub fn peek(&mut self) -> io::Result<Option<&str>> {
+        self.peek_n(0)
+    }
+
+    /// Returns the token `n` tokens after the next one without consuming
+    /// any of them, so `peek_n(0)` is the same as `peek`. The tokens read
+    /// ahead are kept until they are returned by `next`, so this should only
+    /// be used to look a few tokens ahead.
+    ///
+    /// ```
+    /// let mut tokenizer = token::Tokenizer::new("a b c".as_bytes(), vec![' ']);
+    /// assert_eq!(Some("c"), tokenizer.peek_n(2).unwrap());
+    /// assert_eq!(None,      tokenizer.peek_n(3).unwrap());
+    /// assert_eq!(Some("a"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("b"), tokenizer.next().unwrap());
+    /// ```
+    pub fn peek_n(&mut self, n: usize) -> io::Result<Option<&str>> {
+        self.begin_call();
+        while self.peeked.len() <= n && self.peeked.back().is_none_or(|p| p.found) {
+            // Keep what was read for the last token until the peeked one is
+            // returned
+            let gap = mem::take(&mut self.gap);
+            let span = self.span;
+            let record = self.record;
+            let mut token = String::new();
+            let found = self.read_next(&mut token);
+            let peeked = Peeked {
+                token,
+                gap: mem::replace(&mut self.gap, gap),
+                span: mem::replace(&mut self.span, span),
+                record: mem::replace(&mut self.record, record),
+                found: found?,
+            };
+            self.peeked.push_back(peeked);
+        }
+        Ok(self.peeked.get(n).filter(|p| p.found).map(|p| &p.token[..]))
+    }
+
+    /// Pushes the last token returned by `next` back, so that the next call
+    /// returns it again, for parsers that have to back up after reading one
+    /// token too many. Only that one token can be pushed back, and this
+    /// returns whether there was one to push back.
+    ///
+    /// ```
+    /// let mut tokenizer = token::Tokenizer::new("let x".as_bytes(), vec![' ']);
+    /// assert_eq!(Some("let"), tokenizer.next().unwrap());
+    /// assert!(tokenizer.unread_token());
+    /// assert!(!tokenizer.unread_token());
+    /// assert_eq!(Some("let"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("x"),   tokenizer.next().unwrap());
+    /// ```
+    pub fn unread_token(&mut self) -> bool {
+        if !self.unreadable {
+            return false;
+        }
+        self.unreadable = false;
+        self.peeked.push_front(Peeked {
+            token: self.current.clone(),
+            gap: self.gap.clone(),
+            span: self.span,
+            record: self.record,
+            found: true,
+        });
+        true
+    }
+
+    /// Returns the next token or gap, so that the pieces cover the whole
+    /// input, byte for byte. Gaps are never empty, and a gap is always
+    /// followed by a token or the end of the input.
+    ///
+    /// ```
+    /// use token::Piece;
+    ///
+    /// let source: &str = " Hello,\tworld ";
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(source.as_bytes(), vec![' ', '\t']);
+    /// assert_eq!(Some(Piece::Gap(" ")),         tokenizer.next_piece().unwrap());
+    /// assert_eq!(Some(Piece::Content("Hello,")), tokenizer.next_piece().unwrap());
+    /// assert_eq!(Some(Piece::Gap("\t")),        tokenizer.next_piece().unwrap());
+    /// assert_eq!(Some(Piece::Content("world")),  tokenizer.next_piece().unwrap());
+    /// assert_eq!(Some(Piece::Gap(" ")),         tokenizer.next_piece().unwrap());
+    /// assert_eq!(None,                          tokenizer.next_piece().unwrap());
+    /// ```
+    pub fn next_piece(&mut self) -> io::Result<Option<Piece<'_>>> {
+        if self.content_pending {
+            self.content_pending = false;
+            return Ok(Some(Piece::Content(&self.current)));
+        }
+        self.begin_call();
+        let found = self.next_token()?.is_some();
+        if self.gap.is_empty() {
+            Ok(if found { Some(Piece::Content(&self.current)) } else { None })
+        } else {
+            // Hold on to the token until the gap before it has been returned
+            self.content_pending = found;
+            Ok(Some(Piece::Gap(&self.gap)))
+        }
+    }
+
+    fn next_token(&mut self) -> io::Result<Option<&str>> {
+        let mut current = mem::take(&mut self.current);
+        current.clear();
+        let found = self.read_token(&mut current);
+        self.current = current;
+        self.unreadable = false;
+        if found? {
+            self.unreadable = true;
+            Ok(Some(&self.current))
+        } else {
+            Ok(None) // No more chars left
+        }
+    }
+
+    /// Appends the next token to `out`, and returns whether there was one.
+    /// This lets the sentence splitter fill its buffer without a copy.
+    fn read_token(&mut self, out: &mut String) -> io::Result<bool> {
+        match self.peeked.pop_front() {
+            Some(peeked) => {
+                out.push_str(&peeked.token);
+                self.gap = peeked.gap;
+                self.span = peeked.span;
+                self.record = peeked.record;
+                Ok(peeked.found)
+            }
+            None => self.read_next(out),
+        }
+    }
+
+    /// Reads the next token from the input, past any that were peeked at
+    fn read_next(&mut self, out: &mut String) -> io::Result<bool> {
+        let start = out.len();
+        self.gap.clear();
+        // The separator that ended the last token starts this gap
+        self.gap.push_str(&self.ended_on);
+        self.ended_on.clear();
+        self.length.truncating = false;
+        // The char a long token was split before starts this token
+        if let Some(c) = self.carried.take() {
+            self.record = self.newlines;
+            self.span = self.at;
+            out.push(c);
+        }
+        loop {
+            if self.lookahead.is_empty() && self.open_quote.is_none() && self.read_buffered(out, start)? {
+                return Ok(true);
+            }
+            let mut c = match self.next_char()? {
+                Some(c) => c,
+                None => break,
+            };
+            // Where the char starts, or the escape before it
+            let from = self.at;
+            // How many chars of separator start at `c`?
+            let mut len = 0;
+            match self.open_quote {
+                // Everything up to the closing quote is part of the token
+                Some(_) if self.escaped => self.escaped = false,
+                Some(_) if self.escape == Some(c) => self.escaped = true,
+                Some(quote) if c == quote => self.open_quote = None,
+                Some(_) => {}
+                None if self.escape == Some(c) => {
+                    if let Some(next) = self.next_char()? {
+                        c = next;
+                    }
+                }
+                None if self.quotes.contains(&c) => self.open_quote = Some(c),
+                None => {
+                    len = usize::from((self.records && c == '\n') || self.lookup.contains(c));
+                    if self.string_starts.contains(c) {
+                        len = len.max(self.string_at(c)?);
+                    }
+                }
+            }
+            if len > 0 {
+                let ends_token = out.len() > start;
+                let mut gap = mem::take(if ends_token { &mut self.ended_on } else { &mut self.gap });
+                let before = gap.len();
+                gap.push(c);
+                // The rest of the separator is in the lookahead
+                for _ in 1..len {
+                    gap.extend(self.next_char()?);
+                }
+                if self.records {
+                    self.newlines += gap[before..].matches('\n').count();
+                }
+                if ends_token {
+                    self.ended_on = gap;
+                    return Ok(true);
+                }
+                self.gap = gap;
+            } else {
+                if self.length.room(out.len() - start, c.len_utf8())? < c.len_utf8() {
+                    if self.length.policy == LongTokens::SplitAtLimit {
+                        self.carried = Some(c);
+                        return Ok(true);
+                    }
+                    continue;
+                }
+                if out.len() == start {
+                    self.record = self.newlines;
+                    self.span = from;
+                }
+                // Just add the char
+                out.push(c);
+                self.span.byte_end = self.at.byte_end;
+            }
+            self.limits.check_buffered(out.len() - start + self.gap.len())?;
+        }
+        if let Some(quote) = self.open_quote.take() {
+            if self.unclosed_quotes == UnclosedQuote::Error {
+                let message = format!("quote {:?} of the token at line {}, column {} is never closed",
+                    quote, self.span.line, self.span.column);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+        }
+        // Handle leftover chars
+        Ok(out.len() > start)
+    }
+
+    /// Reads chars straight from the buffer of the reader, for as long as
+    /// they can be told apart without looking ahead, and returns whether the
+    /// token ended. Runs of ASCII token chars are copied without looking at
+    /// each char on its own, and only sequences split between two fills of
+    /// the buffer, or invalid ones, are left for `next_char`. This is much
+    /// faster than going through `next_char` for every char.
+    fn read_buffered(&mut self, out: &mut String, start: usize) -> io::Result<bool> {
+        let buf = self.chars.buffer()?;
+        let lookup = &self.lookup;
+        let string_starts = &self.string_starts;
+        let quotes = &self.quotes;
+        let escape = self.escape;
+        let stops = &self.stops;
+        let offset = self.at.byte_end;
+        let mut ended = false;
+        let mut used = 0;
+        while used < buf.len() {
+            // Copy a run of ASCII token chars in one go
+            let run = buf[used..].iter().position(|&b| stops[usize::from(b)]).unwrap_or(buf.len() - used);
+            if run > 0 {
+                if out.len() == start {
+                    self.record = self.newlines;
+                    self.span = Span { byte_start: offset + used, byte_end: 0, line: self.line, column: self.column };
+                }
+                let room = self.length.room(out.len() - start, run)?;
+                // ASCII is always valid UTF-8
+                out.push_str(str::from_utf8(&buf[used..used + room]).unwrap_or(""));
+                if room > 0 {
+                    self.span.byte_end = offset + used + room;
+                }
+                if room < run && self.length.policy == LongTokens::SplitAtLimit {
+                    // The rest of the run starts the next token
+                    used += room;
+                    self.column += room;
+                    ended = true;
+                    break;
+                }
+                used += run;
+                self.column += run;
+            }
+
+            // Then look at the char that stopped it
+            let (c, len) = match buf.get(used) {
+                None => break,
+                Some(&b) if b.is_ascii() => (char::from(b), 1),
+                Some(_) => match sequence(&buf[used..]) {
+                    Sequence::Valid(len) => (decode(&buf[used..used + len]), len),
+                    _ => break,
+                },
+            };
+            if string_starts.contains(c) || quotes.contains(&c) || escape == Some(c) {
+                break;
+            }
+            let newline = c == '\n';
+            if (newline && self.records) || lookup.contains(c) {
+                if newline && self.records {
+                    self.newlines += 1;
+                }
+                if out.len() > start {
+                    self.ended_on.push(c);
+                    ended = true;
+                } else {
+                    self.gap.push(c);
+                }
+            } else if self.length.room(out.len() - start, len)? < len {
+                if self.length.policy == LongTokens::SplitAtLimit {
+                    ended = true;
+                    break;
+                }
+            } else {
+                if out.len() == start {
+                    self.record = self.newlines;
+                    self.span = Span { byte_start: offset + used, byte_end: 0, line: self.line, column: self.column };
+                }
+                out.push(c);
+                self.span.byte_end = offset + used + len;
+            }
+            used += len;
+            if newline {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            if ended {
+                break;
+            }
+        }
+        if used == 0 {
+            return Ok(ended);
+        }
+        self.chars.consume(used);
+        // Only the end of the last char matters to `next_char`
+        self.at.byte_end = offset + used;
+        self.count_read(used)?;
+        self.limits.check_buffered(out.len() - start + self.gap.len())?;
+        Ok(ended)
+    }
+
+    /// Counts bytes that were read against the limits
+    fn count_read(&mut self, len: usize) -> io::Result<()> {
+        self.read += len as u64;
+        self.limits.check_input(self.read)?;
+        // Looking at the clock is slow, so only do it once in a while
+        let ticks = self.ticks.wrapping_add(len as u32);
+        let started = if ticks / 1024 != self.ticks / 1024 { self.call_started } else { None };
+        self.ticks = ticks;
+        self.limits.check_call(self.read - self.call_read, started)
+    }
+
+    /// Returns the length in chars of the longest string separator starting
+    /// with `c` at this point of the input, or 0 if none of them match
+    fn string_at(&mut self, c: char) -> io::Result<usize> {
+        let longest = self.strings.iter().filter(|s| s[0] == c).map(|s| s.len()).max().unwrap_or(0);
+        while self.lookahead.len() + 1 < longest {
+            match self.read_char()? {
+                Some(next) => self.lookahead.push_back(next),
+                None => break,
+            }
+        }
+        let lookahead = &self.lookahead;
+        let found = self.strings.iter().find(|s| {
+            s[0] == c && s[1..].iter().copied().eq(lookahead.iter().map(|&(c, _)| c).take(s.len() - 1))
+        });
+        Ok(found.map_or(0, |s| s.len()))
+    }
+
+    /// Returns the next char, from the lookahead if it has any, and moves
+    /// the position past it
+    fn next_char(&mut self) -> io::Result<Option<char>> {
+        let (c, end) = match self.lookahead.pop_front() {
+            Some(next) => next,
+            None => match self.read_char()? {
+                Some(next) => next,
+                None => return Ok(None),
+            },
+        };
+        // Skipped input is not part of the char, but replaced input is
+        let start = self.at.byte_end.max(end.saturating_sub(c.len_utf8()));
+        self.at = Span { byte_start: start, byte_end: end, line: self.line, column: self.column };
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Ok(Some(c))
+    }
+
+    /// Reads the next char from the input, counting the bytes it took
+    /// against the limits, and returns it with the offset of the input after
+    /// it. Invalid input that was skipped or replaced is counted as it was.
+    fn read_char(&mut self) -> io::Result<Option<(char, usize)>> {
+        let before = self.chars.offset();
+        let c = self.chars.next().transpose();
+        self.count_read((self.chars.offset() - before) as usize)?;
+        match c? {
+            Some(c) => Ok(Some((c, self.chars.offset() as usize))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + Seek> Tokenizer<R> {
+    /// Starts over from the start of the input, as with `seek_to_byte(0)`
+    pub fn rewind(&mut self) -> io::Result<()> {
+        self.seek_to_byte(0)
+    }
+
+    /// Starts over from a byte offset of the input, for another pass over a
+    /// file without opening it again. Spans are given from the start of the
+    /// input, but lines and columns are counted as if the input started at
+    /// the offset. An offset in the middle of a char gives invalid UTF-8.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(Cursor::new("one two three"), vec![' ']);
+    /// assert_eq!(Some("one"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("two"), tokenizer.next().unwrap());
+    ///
+    /// tokenizer.rewind().unwrap();
+    /// assert_eq!(Some("one"), tokenizer.next().unwrap());
+    ///
+    /// tokenizer.seek_to_byte(8).unwrap();
+    /// let (token, span) = tokenizer.next_with_span().unwrap().unwrap();
+    /// assert_eq!("three", token);
+    /// assert_eq!((8, 13), (span.byte_start, span.byte_end));
+    /// ```
+    pub fn seek_to_byte(&mut self, offset: u64) -> io::Result<()> {
+        self.chars.seek(offset)?;
+        self.clear();
+        let offset = offset as usize;
+        self.at = Span { byte_start: offset, byte_end: offset, line: 1, column: 1 };
+        Ok(())
+    }
+}
+
+/// An iterator over the tokens of a `Tokenizer` as owned strings, returned
+/// by `Tokenizer::owned`
+#[cfg(feature = "std")]
+pub struct OwnedTokens<R: Read> {
+    tokenizer: Tokenizer<R>,
+    failed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> OwnedTokens<R> {
+    /// Returns the tokenizer, to reuse it on a new reader
+    pub fn into_inner(self) -> Tokenizer<R> {
+        self.tokenizer
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for OwnedTokens<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        if self.failed {
+            return None;
+        }
+        match self.tokenizer.next() {
+            Ok(token) => token.map(|token| Ok(token.to_string())),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> IntoIterator for Tokenizer<R> {
+    type Item = io::Result<String>;
+    type IntoIter = OwnedTokens<R>;
+
+    fn into_iter(self) -> OwnedTokens<R> {
+        self.owned()
+    }
+}
+
+/// A builder of tokenizers, with presets for common kinds of text
+///
+/// ```
+/// use token::TokenizerBuilder;
+///
+/// let source: &str = "let x=foo(bar, baz_qux);";
+/// let mut tokenizer = TokenizerBuilder::identifiers().build(source.as_bytes());
+/// assert_eq!(Some("let"),     tokenizer.next().unwrap());
+/// assert_eq!(Some("x"),       tokenizer.next().unwrap());
+/// assert_eq!(Some("foo"),     tokenizer.next().unwrap());
+/// assert_eq!(Some("bar"),     tokenizer.next().unwrap());
+/// assert_eq!(Some("baz_qux"), tokenizer.next().unwrap());
+/// assert_eq!(None,            tokenizer.next().unwrap());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TokenizerBuilder {
+    separators: Vec<char>,
+    strings: Vec<String>,
+    unicode_whitespace: bool,
+    records: bool,
+    limits: Limits,
+    max_token_len: Option<usize>,
+    long_tokens: LongTokens,
+    invalid_utf8: InvalidUtf8,
+    quotes: Vec<char>,
+    unclosed_quotes: UnclosedQuote,
+    escape: Option<char>,
+}
+
+#[cfg(feature = "std")]
+impl TokenizerBuilder {
+    /// Creates a builder without any separators
+    pub fn new() -> TokenizerBuilder {
+        TokenizerBuilder::default()
+    }
+
+    /// A builder for prose, separating tokens on Unicode whitespace only, so
+    /// that punctuation stays attached for a `SentenceSplitter`
+    pub fn prose() -> TokenizerBuilder {
+        TokenizerBuilder::new().unicode_whitespace()
+    }
+
+    /// A builder for the fields of comma-separated values. Quoted fields are
+    /// not handled.
+    ///
+    /// ```
+    /// let mut tokenizer = token::TokenizerBuilder::csv().build("a,b\nc,d".as_bytes());
+    /// assert_eq!(Some("a"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("b"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("c"), tokenizer.next().unwrap());
+    /// assert_eq!(1, tokenizer.record());
+    /// ```
+    pub fn csv() -> TokenizerBuilder {
+        TokenizerBuilder::new().separators(&[',', '\r']).records()
+    }
+
+    /// A builder for log files, separating tokens on whitespace, with each
+    /// line as a record
+    pub fn log_lines() -> TokenizerBuilder {
+        TokenizerBuilder::new().whitespace().records()
+    }
+
+    /// A builder for source code, separating tokens on whitespace and all
+    /// ASCII punctuation except underscores, so that only identifiers,
+    /// keywords and numbers are left
+    pub fn identifiers() -> TokenizerBuilder {
+        TokenizerBuilder::new().whitespace().punctuation().without(&['_'])
+    }
+
+    /// Adds the given separators
+    pub fn separators(mut self, separators: &[char]) -> TokenizerBuilder {
+        for &c in separators {
+            if !self.separators.contains(&c) {
+                self.separators.push(c);
+            }
+        }
+        self
+    }
+
+    /// Removes the given separators, if they were added
+    pub fn without(mut self, separators: &[char]) -> TokenizerBuilder {
+        self.separators.retain(|c| !separators.contains(c));
+        self
+    }
+
+    /// Adds separators of one or more chars, as with `Tokenizer::with_strings`
+    pub fn strings(mut self, separators: &[&str]) -> TokenizerBuilder {
+        self.strings.extend(separators.iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Adds the ASCII whitespace characters as separators
+    pub fn whitespace(self) -> TokenizerBuilder {
+        self.separators(&[' ', '\t', '\n', '\r', '\x0b', '\x0c'])
+    }
+
+    /// Adds all Unicode whitespace and zero width spaces as separators, as
+    /// with `Tokenizer::unicode_whitespace`
+    pub fn unicode_whitespace(mut self) -> TokenizerBuilder {
+        self.unicode_whitespace = true;
+        self.separators(&['\u{200B}'])
+    }
+
+    /// Adds all ASCII punctuation as separators
+    pub fn punctuation(self) -> TokenizerBuilder {
+        let punctuation: Vec<char> = (0x21u8..0x7F).map(char::from)
+            .filter(|c| c.is_ascii_punctuation())
+            .collect();
+        self.separators(&punctuation)
+    }
+
+    /// Treats each line as a separate record, as with `Tokenizer::records`
+    pub fn records(mut self) -> TokenizerBuilder {
+        self.records = true;
+        self
+    }
+
+    /// Makes `"\r\n"` a separator, so that the carriage returns of Windows
+    /// line endings never end up in tokens, while a `'\r'` on its own is
+    /// left alone
+    ///
+    /// ```
+    /// let source: &str = "one\r\ntwo\rthree\r\n";
+    ///
+    /// let mut tokenizer = token::TokenizerBuilder::new().records().crlf().build(source.as_bytes());
+    /// assert_eq!(Some("one"),        tokenizer.next().unwrap());
+    /// assert_eq!(Some("two\rthree"), tokenizer.next().unwrap());
+    /// assert_eq!(1, tokenizer.record());
+    /// assert_eq!(None,               tokenizer.next().unwrap());
+    /// assert_eq!("\r\n",             tokenizer.gap());
+    /// ```
+    pub fn crlf(self) -> TokenizerBuilder {
+        self.strings(&["\r\n"])
+    }
+
+    /// Sets the most bytes a token may hold. Longer tokens are an error
+    /// unless `long_tokens` says otherwise.
+    ///
+    /// ```
+    /// use token::{LongTokens, TokenizerBuilder};
+    ///
+    /// let source: &str = "ok aaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    ///
+    /// let mut tokenizer = TokenizerBuilder::new().whitespace()
+    ///     .max_token_len(16)
+    ///     .build(source.as_bytes());
+    /// assert_eq!(Some("ok"), tokenizer.next().unwrap());
+    /// assert!(tokenizer.next().is_err());
+    ///
+    /// let mut tokenizer = TokenizerBuilder::new().whitespace()
+    ///     .max_token_len(16)
+    ///     .long_tokens(LongTokens::Truncate)
+    ///     .build(source.as_bytes());
+    /// assert_eq!(Some("ok"), tokenizer.next().unwrap());
+    /// assert_eq!(Some("aaaaaaaaaaaaaaaa"), tokenizer.next().unwrap());
+    /// ```
+    pub fn max_token_len(mut self, max: usize) -> TokenizerBuilder {
+        self.max_token_len = Some(max);
+        self
+    }
+
+    /// Sets what to do with tokens longer than the maximum token length
+    pub fn long_tokens(mut self, policy: LongTokens) -> TokenizerBuilder {
+        self.long_tokens = policy;
+        self
+    }
+
+    /// Sets the limits of the tokenizer, as with `Tokenizer::set_limits`
+    pub fn limits(mut self, limits: Limits) -> TokenizerBuilder {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets what the tokenizer does with input that is not valid UTF-8
+    pub fn invalid_utf8(mut self, policy: InvalidUtf8) -> TokenizerBuilder {
+        self.invalid_utf8 = policy;
+        self
+    }
+
+    /// Sets the quote chars and what to do with a quote that is never
+    /// closed, as with `Tokenizer::set_quotes`
+    pub fn quotes(mut self, quotes: &[char], unclosed: UnclosedQuote) -> TokenizerBuilder {
+        self.quotes = quotes.to_vec();
+        self.unclosed_quotes = unclosed;
+        self
+    }
+
+    /// Sets the escape char, as with `Tokenizer::set_escape`
+    pub fn escape(mut self, escape: char) -> TokenizerBuilder {
+        self.escape = Some(escape);
+        self
+    }
+
+    /// Creates a tokenizer for a reader with this configuration
+    pub fn build<R: Read>(&self, reader: R) -> Tokenizer<R> {
+        let mut tokenizer = Tokenizer::new(reader, Vec::new());
+        tokenizer.lookup = SeparatorSet::new(&self.separators);
+        if self.unicode_whitespace {
+            tokenizer.lookup = tokenizer.lookup.with_unicode_whitespace();
+        }
+        tokenizer.add_strings(&self.strings);
+        tokenizer.records = self.records;
+        tokenizer.set_limits(self.limits);
+        tokenizer.set_max_token_len(self.max_token_len, self.long_tokens);
+        tokenizer.set_invalid_utf8(self.invalid_utf8);
+        tokenizer.set_quotes(self.quotes.clone(), self.unclosed_quotes);
+        tokenizer.set_escape(self.escape);
+        tokenizer
+    }
+}
+
+/// A tokenizer for text that is already in memory, returning slices of the
+/// text itself instead of copying each token
+///
+/// ```
+/// let text = String::from("  Hello world \n  How do you do");
+///
+/// let tokens: Vec<&str> = token::StrTokenizer::new(&text, vec![' ', '\n']).collect();
+/// assert_eq!(vec!["Hello", "world", "How", "do", "you", "do"], tokens);
+/// ```
+pub struct StrTokenizer<'a> {
+    text: &'a str,
+    pos: usize,
+    lookup: SeparatorSet,
+    bytes: Option<[bool; 256]>,
+    gap: &'a str,
+}
+
+impl<'a> StrTokenizer<'a> {
+    /// Creates a new tokenizer over a string and a set of separating chars
+    pub fn new(text: &'a str, separators: Vec<char>) -> StrTokenizer<'a> {
+        let lookup = SeparatorSet::new(&separators);
+        let bytes = lookup.byte_table();
+        StrTokenizer { text, pos: 0, lookup, bytes, gap: "" }
+    }
+
+    /// Creates a new tokenizer over a string that separates tokens like
+    /// `Tokenizer::unicode_whitespace`
+    pub fn unicode_whitespace(text: &'a str) -> StrTokenizer<'a> {
+        let lookup = SeparatorSet::new(&['\u{200B}']).with_unicode_whitespace();
+        StrTokenizer { text, pos: 0, lookup, bytes: None, gap: "" }
+    }
+
+    /// Creates a new tokenizer over a string that separates tokens on the
+    /// chars the matcher accepts, like `Tokenizer::with_matcher`
+    pub fn with_matcher<M>(text: &'a str, matcher: M) -> StrTokenizer<'a>
+        where M: SeparatorMatcher + Send + 'static
+    {
+        let mut tokenizer = StrTokenizer::new(text, Vec::new());
+        tokenizer.lookup.matcher = Some(Box::new(matcher));
+        tokenizer.bytes = None;
+        tokenizer
+    }
+
+    /// Returns the separators before the last token, or after the last
+    /// token once the text is exhausted, like `Tokenizer::gap`
+    ///
+    /// ```
+    /// let mut tokenizer = token::StrTokenizer::new("a,,b,", vec![',']);
+    /// assert_eq!(Some("a"), tokenizer.next());
+    /// assert_eq!(Some("b"), tokenizer.next());
+    /// assert_eq!(",,",      tokenizer.gap());
+    /// assert_eq!(None,      tokenizer.next());
+    /// assert_eq!(",",       tokenizer.gap());
+    /// ```
+    pub fn gap(&self) -> &'a str {
+        self.gap
+    }
+
+    /// Returns the byte offset in the text where the next token is looked
+    /// for, which is right after the last token
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a> Iterator for StrTokenizer<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = &self.text[self.pos..];
+        // Separator bytes are ASCII, so they are always whole chars
+        let first = match &self.bytes {
+            Some(table) => rest.bytes().position(|b| !table[usize::from(b)]),
+            None => rest.char_indices().find(|&(_, c)| !self.lookup.contains(c)).map(|(i, _)| i),
+        };
+        let start = match first {
+            Some(i) => self.pos + i,
+            None => {
+                self.gap = rest;
+                self.pos = self.text.len();
+                return None;
+            }
+        };
+        let len = match &self.bytes {
+            Some(table) => self.text[start..].bytes().position(|b| table[usize::from(b)]),
+            None => self.text[start..].char_indices().find(|&(_, c)| self.lookup.contains(c)).map(|(i, _)| i),
+        };
+        let end = len.map_or(self.text.len(), |i| start + i);
+        self.gap = &self.text[self.pos..start];
+        self.pos = end;
+        Some(&self.text[start..end])
+    }
+}
+
+/// Why a sentence ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Break<'a> {
+    /// The last token ended with this terminator
+    Terminator(&'a str),
+    /// The last token closed this quote
+    Quote(&'a str),
+    /// The next token belongs to a new record
+    Record,
+    /// The next token is on a new line, and newlines are hard boundaries
+    Newline,
+    /// The next token is after a blank line, and paragraphs are breaks
+    Paragraph,
+    /// There was no more input
+    End,
+}
+
+/// A boundary rule of a `SentenceSplitter`, which can be turned on and off
+/// with `SentenceSplitter::set_rule_enabled`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rule {
+    /// Sentences never continue into a new record
+    Records,
+    /// A blank line ends a sentence
+    Paragraphs,
+    /// A new line ends a sentence
+    Newlines,
+    /// A quote is part of one sentence, and closing it ends the sentence
+    Quotes,
+    /// A token ending in `..` doesn't end a sentence
+    ThoughtTrails,
+    /// A token ending in a terminator ends a sentence
+    Terminators,
+    /// A known abbreviation doesn't end a sentence
+    Abbreviations,
+    /// An initial, such as "J.", doesn't end a sentence
+    Initials,
+    /// A Roman numeral numbering a chapter doesn't end a sentence
+    RomanNumerals,
+    /// An ordinal number before a month doesn't end a sentence
+    OrdinalDates,
+    /// A sentence goes on before a lower case word
+    LowercaseContinues,
+    /// A clause terminator ends a sentence before a capitalized word
+    ClauseTerminators,
+}
+
+impl Rule {
+    /// All the rules, in the order the splitter applies them to a token
+    pub const ALL: [Rule; 12] = [
+        Rule::Records, Rule::Paragraphs, Rule::Newlines, Rule::Quotes,
+        Rule::ThoughtTrails, Rule::Terminators, Rule::Abbreviations,
+        Rule::Initials, Rule::RomanNumerals, Rule::OrdinalDates,
+        Rule::LowercaseContinues, Rule::ClauseTerminators,
+    ];
+
+    /// Returns the name of the rule, for showing to users
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Rule::Records => "records",
+            Rule::Paragraphs => "paragraphs",
+            Rule::Newlines => "newlines",
+            Rule::Quotes => "quotes",
+            Rule::ThoughtTrails => "thought trails",
+            Rule::Terminators => "terminators",
+            Rule::Abbreviations => "abbreviations",
+            Rule::Initials => "initials",
+            Rule::RomanNumerals => "roman numerals",
+            Rule::OrdinalDates => "ordinal dates",
+            Rule::LowercaseContinues => "lowercase continues",
+            Rule::ClauseTerminators => "clause terminators",
+        }
+    }
+}
+
+/// A rule of a splitter together with its parameters, as returned by
+/// `SentenceSplitter::rules`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RuleInfo<'a> {
+    /// The rule
+    pub rule: Rule,
+    /// Whether the splitter applies the rule
+    pub enabled: bool,
+    /// The strings the rule looks for, such as the terminators or the
+    /// abbreviations, for the rules that have them
+    pub strings: Vec<&'a str>,
+    /// The most tokens a quote may span, for `Rule::Quotes`
+    pub max_tokens: Option<usize>,
+}
+
+/// Abbreviations common in legal citations, as used by
+/// `SentenceSplitter::legal`
+pub const LEGAL_ABBREVIATIONS: [&str; 34] = [
+    "U.S.", "U.S.C.", "C.F.R.", "Stat.", "Pub.", "L.", "Reg.", "Fed.", "F.",
+    "F.2d", "F.3d", "F.4th", "Supp.", "Ct.", "App.", "Cir.", "Dist.", "Cal.",
+    "N.Y.", "Tex.", "v.", "No.", "Nos.", "Sec.", "Art.", "Ch.", "Cl.", "Para.",
+    "Id.", "id.", "Inc.", "Corp.", "Co.", "al.",
+];
+
+/// Abbreviations common in biomedical text, as used by
+/// `SentenceSplitter::biomedical`
+pub const BIOMEDICAL_ABBREVIATIONS: [&str; 30] = [
+    "Fig.", "Figs.", "fig.", "figs.", "Tab.", "Eq.", "Eqs.", "Ref.", "Refs.",
+    "Suppl.", "al.", "e.g.", "i.e.", "etc.", "vs.", "cf.", "ca.", "approx.",
+    "resp.", "sp.", "spp.", "subsp.", "var.", "gen.", "nov.", "no.", "No.",
+    "vol.", "Dr.", "Prof.",
+];
+
+/// Abbreviations common in German, as used by `SentenceSplitter::german`
+pub const GERMAN_ABBREVIATIONS: [&str; 26] = [
+    "z.B.", "bzw.", "usw.", "vgl.", "ca.", "Nr.", "Dr.", "Prof.", "Hr.", "Fr.",
+    "Str.", "S.", "u.a.", "d.h.", "evtl.", "ggf.", "inkl.", "sog.", "Jh.",
+    "Abs.", "St.", "Tel.", "etc.", "z.T.", "u.U.", "v.a.",
+];
+
+/// The months of the year in German, which follow ordinal numbers in dates
+/// such as "am 3. Juli", as used by `SentenceSplitter::german`
+pub const GERMAN_MONTHS: [&str; 12] = [
+    "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August",
+    "September", "Oktober", "November", "Dezember",
+];
+
+/// Abbreviations common in French, as used by `SentenceSplitter::french`
+pub const FRENCH_ABBREVIATIONS: [&str; 22] = [
+    "M.", "MM.", "Mme.", "Mlle.", "Dr.", "Pr.", "St.", "Ste.", "av.", "bd.",
+    "p.", "pp.", "cf.", "etc.", "env.", "ex.", "vol.", "chap.", "éd.", "art.",
+    "hab.", "apr.",
+];
+
+/// Abbreviations common in Spanish, as used by `SentenceSplitter::spanish`
+pub const SPANISH_ABBREVIATIONS: [&str; 24] = [
+    "Sr.", "Sra.", "Srta.", "Sres.", "Dr.", "Dra.", "Ud.", "Uds.", "Lic.",
+    "Ing.", "Prof.", "D.", "Dña.", "pág.", "págs.", "etc.", "p.ej.", "aprox.",
+    "núm.", "tel.", "EE.UU.", "Av.", "Cía.", "S.A.",
+];
+
+/// A structure for iteratively splitting stringy things into sentences
+#[cfg(feature = "std")]
+pub struct SentenceSplitter<'a, R: Read> {
+    tokenizer: Tokenizer<R>,
+    terminators: Vec<&'a str>,
+    clause_terminators: Vec<&'a str>,
+    clause: Option<&'a str>,
+    lowercase_continues: bool,
+    deferred: Option<&'a str>,
+    abbreviations: Vec<&'a str>,
+    initials: bool,
+    roman_numerals: bool,
+    ordinal_months: Vec<&'a str>,
+    /// Whether the deferred terminator ends an ordinal number
+    ordinal: bool,
+    /// Whether the end of the sentence depended on the whole of the token
+    /// after it, not just on its first char
+    looked_ahead: bool,
+    hard_newlines: bool,
+    /// Whether only the span of the sentence is needed, so that its text
+    /// can be dropped as it is read
+    scanning: bool,
+    /// The rules with parameters that were turned off
+    disabled: Vec<Rule>,
+    paragraphs: bool,
+    current: String,
+    quotes: Vec<&'a str>,
+    record: usize,
+    held: bool,
+    last: Break<'a>,
+    max_quote_tokens: Option<usize>,
+    bounds: Vec<(usize, usize)>,
+    spans: Vec<Span>,
+    quote_from: usize,
+    /// The quotes closed by another string than they are opened by
+    closing_quotes: Vec<(&'a str, &'a str)>,
+    replay: VecDeque<(String, Span)>,
+    recoveries: usize,
+    /// Where the quote given up on last started, and where the token that
+    /// made it go past the limit ended
+    recovered: Option<(usize, usize)>,
+    checksum: Option<Normalization>,
+}
+
+#[cfg(feature = "std")]
+impl <'a, R: Read> SentenceSplitter<'a, R> {
+
+    /// Creates a new sentence-splitting iterator
+    ///
+    /// ```
+    /// let separators  = vec![' ', '\n', '\t', '\r'];
+    /// let terminators = vec![".", "!", "?"];
+    /// let quotes = vec![]; // For when whole lines are quoted
+    ///
+    /// let text = "I walked down the road.\n\"What did he say\", she asked me.\n\"Nothing\", I replied, and continued walking...\nit wasn't any of my business.\nOr was it?";
+    ///
+    /// let mut tokenizer = token::Tokenizer::new(text.as_bytes(), separators);
+    /// let mut splitter = token::SentenceSplitter::new(
+    ///     tokenizer, terminators, quotes
+    /// );
+    ///
+    /// assert_eq!(Some("I walked down the road."), splitter.next().unwrap());
+    /// assert_eq!(Some("\"What did he say\", she asked me."),
+    ///            splitter.next().unwrap());
+    /// assert_eq!(Some("\"Nothing\", I replied, and continued walking... \
+    ///                  it wasn't any of my business."),
+    ///            splitter.next().unwrap());
+    /// assert_eq!(Some("Or was it?"), splitter.next().unwrap());
+    /// assert_eq!(None, splitter.next().unwrap());
+    /// ```
+    pub fn new(source: Tokenizer<R>, terminators: Vec<&'a str>,
+               quotes: Vec<&'a str>) -> SentenceSplitter<'a, R>
+    {
+        SentenceSplitter{
+            tokenizer: source,
+            current: String::new(),
+            terminators,
+            clause_terminators: Vec::new(),
+            clause: None,
+            lowercase_continues: false,
+            deferred: None,
+            abbreviations: Vec::new(),
+            initials: false,
+            roman_numerals: false,
+            ordinal_months: Vec::new(),
+            ordinal: false,
+            looked_ahead: false,
+            hard_newlines: false,
+            scanning: false,
+            disabled: Vec::new(),
+            paragraphs: false,
+            quotes,
+            record: 0,
+            held: false,
+            last: Break::End,
+            max_quote_tokens: None,
+            bounds: Vec::new(),
+            spans: Vec::new(),
+            quote_from: 0,
+            closing_quotes: Vec::new(),
+            replay: VecDeque::new(),
+            recoveries: 0,
+            recovered: None,
+            checksum: None,
+        }
+    }
+
+    /// Sets how many tokens a quote may span before the splitter decides it
+    /// was never closed, and splits its tokens again as if the quote wasn't
+    /// there. Without a limit, a stray quote makes the rest of the input a
+    /// single sentence.
+    ///
+    /// ```
+    /// let text = "He said \"hi. Then he left. The end.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(
+    ///     tokenizer, vec!["."], vec!["\""]
+    /// );
+    /// splitter.set_max_quote_tokens(Some(4));
+    ///
+    /// assert_eq!(Some("He said \"hi."), splitter.next().unwrap());
+    /// assert_eq!(Some("Then he left."), splitter.next().unwrap());
+    /// assert_eq!(Some("The end."), splitter.next().unwrap());
+    /// assert_eq!(1, splitter.quote_recoveries());
+    /// ```
+    pub fn set_max_quote_tokens(&mut self, max: Option<usize>) {
+        self.max_quote_tokens = max;
+    }
+
+    /// Sets the string that closes a quote, for quotes that aren't closed by
+    /// the string that opens them, such as `«` and `»`. The quote is added
+    /// if it isn't one of the quotes already.
+    ///
+    /// ```
+    /// let text = "He said «Stop. Now.» Then he left.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_closing_quote("«", "»");
+    ///
+    /// assert_eq!(Some("He said «Stop. Now.»"), splitter.next().unwrap());
+    /// assert_eq!(Some("Then he left."), splitter.next().unwrap());
+    /// ```
+    pub fn set_closing_quote(&mut self, quote: &'a str, closing: &'a str) {
+        if !self.quotes.contains(&quote) {
+            self.quotes.push(quote);
+        }
+        self.closing_quotes.retain(|&(q, _)| q != quote);
+        self.closing_quotes.push((quote, closing));
+    }
+
+    /// Returns the string that closes a quote
+    fn closing(&self, quote: &'a str) -> &'a str {
+        self.closing_quotes.iter().find(|&&(q, _)| q == quote).map_or(quote, |&(_, closing)| closing)
+    }
+
+    /// Creates a sentence splitter for legal text, which knows the
+    /// abbreviations of `LEGAL_ABBREVIATIONS` and initials, so that
+    /// citations such as `42 U.S.C. § 1983` stay in one sentence
+    ///
+    /// ```
+    /// let text = "See 42 U.S.C. § 1983. The court held, in Smith v. Jones, 5 F.3d 1 \
+    ///             (9th Cir. 1993), that it did.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::legal(tokenizer);
+    ///
+    /// assert_eq!(Some("See 42 U.S.C. § 1983."), splitter.next().unwrap());
+    /// assert_eq!(Some("The court held, in Smith v. Jones, 5 F.3d 1 (9th Cir. 1993), that it did."),
+    ///            splitter.next().unwrap());
+    /// ```
+    pub fn legal(source: Tokenizer<R>) -> SentenceSplitter<'a, R> {
+        let mut splitter = SentenceSplitter::new(source, vec![".", "!", "?"], vec![]);
+        splitter.set_abbreviations(LEGAL_ABBREVIATIONS.to_vec());
+        splitter.set_initials(true);
+        splitter
+    }
+
+    /// Creates a sentence splitter for biomedical text, such as the abstracts
+    /// of papers. It knows the abbreviations of `BIOMEDICAL_ABBREVIATIONS`
+    /// and initials, such as the genus of "E. coli", and doesn't end a
+    /// sentence before a lower case word, as after other abbreviations of
+    /// units and measurements.
+    ///
+    /// ```
+    /// let text = "Growth of E. coli was measured by Smith et al. (Fig. 2). \
+    ///             Samples of 2.5 mg were used, e.g. in vitro. Approx. half died.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::biomedical(tokenizer);
+    ///
+    /// assert_eq!(Some("Growth of E. coli was measured by Smith et al. (Fig. 2)."),
+    ///            splitter.next().unwrap());
+    /// assert_eq!(Some("Samples of 2.5 mg were used, e.g. in vitro."), splitter.next().unwrap());
+    /// assert_eq!(Some("Approx. half died."), splitter.next().unwrap());
+    /// ```
+    pub fn biomedical(source: Tokenizer<R>) -> SentenceSplitter<'a, R> {
+        let mut splitter = SentenceSplitter::new(source, vec![".", "!", "?"], vec![]);
+        splitter.set_abbreviations(BIOMEDICAL_ABBREVIATIONS.to_vec());
+        splitter.set_initials(true);
+        splitter.set_lowercase_continues(true);
+        splitter
+    }
+
+    /// Creates a sentence splitter for German, which knows the abbreviations
+    /// of `GERMAN_ABBREVIATIONS`, and ordinal numbers in dates, before the
+    /// months of `GERMAN_MONTHS`. As sentences start with a capital letter,
+    /// one doesn't end before a lower case word.
+    ///
+    /// ```
+    /// let text = "Wir treffen uns am 3. Juli, z.B. im Park. Am 4. kommt er. \
+    ///             Es waren 12. Dann ging er.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::german(tokenizer);
+    ///
+    /// assert_eq!(Some("Wir treffen uns am 3. Juli, z.B. im Park."), splitter.next().unwrap());
+    /// assert_eq!(Some("Am 4. kommt er."), splitter.next().unwrap());
+    /// assert_eq!(Some("Es waren 12."), splitter.next().unwrap());
+    /// assert_eq!(Some("Dann ging er."), splitter.next().unwrap());
+    /// ```
+    pub fn german(source: Tokenizer<R>) -> SentenceSplitter<'a, R> {
+        let mut splitter = SentenceSplitter::new(source, vec![".", "!", "?"], vec![]);
+        splitter.set_abbreviations(GERMAN_ABBREVIATIONS.to_vec());
+        splitter.set_ordinal_months(GERMAN_MONTHS.to_vec());
+        splitter.set_lowercase_continues(true);
+        splitter
+    }
+
+    /// Creates a sentence splitter for French, which knows the abbreviations
+    /// of `FRENCH_ABBREVIATIONS` and initials, so that a title such as
+    /// "M. Dupont" stays in the sentence. A sentence doesn't end before a
+    /// lower case word, and a quote in guillemets is part of one sentence.
+    ///
+    /// ```
+    /// let text = "M. Dupont est arrivé avec Mme. Martin, cf. p. 12. Il pleuvait.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::french(tokenizer);
+    ///
+    /// assert_eq!(Some("M. Dupont est arrivé avec Mme. Martin, cf. p. 12."),
+    ///            splitter.next().unwrap());
+    /// assert_eq!(Some("Il pleuvait."), splitter.next().unwrap());
+    ///
+    /// let text = "Il a dit « Bonjour. » Puis il est parti.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::french(tokenizer);
+    ///
+    /// assert_eq!(Some("Il a dit « Bonjour. »"), splitter.next().unwrap());
+    /// assert_eq!(Some("Puis il est parti."), splitter.next().unwrap());
+    /// ```
+    pub fn french(source: Tokenizer<R>) -> SentenceSplitter<'a, R> {
+        let mut splitter = SentenceSplitter::new(source, vec![".", "!", "?", "…"], vec![]);
+        splitter.set_closing_quote("«", "»");
+        splitter.set_abbreviations(FRENCH_ABBREVIATIONS.to_vec());
+        splitter.set_initials(true);
+        splitter.set_lowercase_continues(true);
+        splitter
+    }
+
+    /// Creates a sentence splitter for Spanish, which knows the abbreviations
+    /// of `SPANISH_ABBREVIATIONS`, such as the title of "Sr. García", and
+    /// initials. A sentence doesn't end before a lower case word.
+    ///
+    /// ```
+    /// let text = "El Sr. García vive en EE.UU. desde 2010. ¿Dónde vive la Dra. López? En Madrid.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::spanish(tokenizer);
+    ///
+    /// assert_eq!(Some("El Sr. García vive en EE.UU. desde 2010."), splitter.next().unwrap());
+    /// assert_eq!(Some("¿Dónde vive la Dra. López?"), splitter.next().unwrap());
+    /// assert_eq!(Some("En Madrid."), splitter.next().unwrap());
+    /// ```
+    pub fn spanish(source: Tokenizer<R>) -> SentenceSplitter<'a, R> {
+        let mut splitter = SentenceSplitter::new(source, vec![".", "!", "?"], vec![]);
+        splitter.set_abbreviations(SPANISH_ABBREVIATIONS.to_vec());
+        splitter.set_initials(true);
+        splitter.set_lowercase_continues(true);
+        splitter
+    }
+
+    /// Sets whether a sentence goes on past a terminator when the next token
+    /// starts with a lower case letter, as after abbreviations that aren't
+    /// known. The splitter then has to read the next token before it can
+    /// return a sentence.
+    ///
+    /// ```
+    /// let text = "It weighs approx. two kg. Then it broke.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_lowercase_continues(true);
+    ///
+    /// assert_eq!(Some("It weighs approx. two kg."), splitter.next().unwrap());
+    /// assert_eq!(Some("Then it broke."), splitter.next().unwrap());
+    /// ```
+    pub fn set_lowercase_continues(&mut self, enabled: bool) {
+        self.lowercase_continues = enabled;
+    }
+
+    /// Sets tokens, such as `"Dr."` or `"etc."`, that never end a sentence
+    /// even though they end in a terminator. Punctuation before them, such
+    /// as an opening parenthesis, is ignored.
+    ///
+    /// ```
+    /// let text = "Ask Dr. Smith. She knows.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_abbreviations(vec!["Dr."]);
+    ///
+    /// assert_eq!(Some("Ask Dr. Smith."), splitter.next().unwrap());
+    /// ```
+    pub fn set_abbreviations(&mut self, abbreviations: Vec<&'a str>) {
+        self.abbreviations = abbreviations;
+    }
+
+    /// Sets terminators that only end a sentence when the next token starts
+    /// with an upper case letter or is on a new line, such as the colons
+    /// and semicolons that separate clauses in legal and biblical texts
+    ///
+    /// ```
+    /// let text = "Whereas the parties agree; and whereas: Now therefore it is agreed";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_clause_terminators(vec![":", ";"]);
+    ///
+    /// assert_eq!(Some("Whereas the parties agree; and whereas:"), splitter.next().unwrap());
+    /// assert_eq!(Some("Now therefore it is agreed"), splitter.next().unwrap());
+    /// ```
+    pub fn set_clause_terminators(&mut self, terminators: Vec<&'a str>) {
+        self.clause_terminators = terminators;
+    }
+
+    /// Sets whether a single upper case letter before a terminator ending in
+    /// `.` is taken to be an initial, which doesn't end the sentence. This
+    /// also keeps sentences that end with a lone "I." going.
+    ///
+    /// ```
+    /// let text = "J. R. R. Tolkien wrote it. Then he left.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_initials(true);
+    ///
+    /// assert_eq!(Some("J. R. R. Tolkien wrote it."), splitter.next().unwrap());
+    /// assert_eq!(Some("Then he left."), splitter.next().unwrap());
+    /// ```
+    pub fn set_initials(&mut self, enabled: bool) {
+        self.initials = enabled;
+    }
+
+    /// Sets whether a Roman numeral before a terminator ending in `.` is
+    /// taken to number a chapter or a list item, which doesn't end the
+    /// sentence. This is only the case at the start of a sentence, or after
+    /// a capitalized word such as "Chapter".
+    ///
+    /// ```
+    /// let text = "Chapter IV. The Return was long. It had XII pages.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_roman_numerals(true);
+    ///
+    /// assert_eq!(Some("Chapter IV. The Return was long."), splitter.next().unwrap());
+    /// assert_eq!(Some("It had XII pages."), splitter.next().unwrap());
+    /// ```
+    pub fn set_roman_numerals(&mut self, enabled: bool) {
+        self.roman_numerals = enabled;
+    }
+
+    /// Sets the names of months that may follow an ordinal number written
+    /// with a period, as in German dates such as "am 3. Juli". The period
+    /// after a number then only ends the sentence if the next token isn't
+    /// one of the months.
+    ///
+    /// ```
+    /// let text = "Es war der 9. November. Am 10. war alles anders.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_ordinal_months(vec!["November"]);
+    ///
+    /// assert_eq!(Some("Es war der 9. November."), splitter.next().unwrap());
+    /// assert_eq!(Some("Am 10."), splitter.next().unwrap());
+    /// ```
+    pub fn set_ordinal_months(&mut self, months: Vec<&'a str>) {
+        self.ordinal_months = months;
+    }
+
+    /// Sets whether a new line always ends a sentence, even without a
+    /// terminator, as in short texts such as messages and posts where every
+    /// line stands on its own. The tokenizer must have `'\n'` among its
+    /// separators for the splitter to see the lines.
+    ///
+    /// ```
+    /// use token::Break;
+    ///
+    /// let text = "just landed\nso tired. need coffee\n\nsee you all tomorrow";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ', '\n']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_hard_newline_boundary(true);
+    ///
+    /// assert_eq!(Some("just landed"), splitter.next().unwrap());
+    /// assert_eq!(Break::Newline, splitter.last_break());
+    /// assert_eq!(Some("so tired."), splitter.next().unwrap());
+    /// assert_eq!(Some("need coffee"), splitter.next().unwrap());
+    /// assert_eq!(Some("see you all tomorrow"), splitter.next().unwrap());
+    /// ```
+    pub fn set_hard_newline_boundary(&mut self, enabled: bool) {
+        self.hard_newlines = enabled;
+    }
+
+    /// Sets whether a blank line, or any other run of separators with more
+    /// than one newline in it, always ends a sentence, so that headings and
+    /// paragraphs without a terminator don't run into the next one. As with
+    /// `set_hard_newline_boundary`, `'\n'` must be a separator.
+    ///
+    /// ```
+    /// use token::Break;
+    ///
+    /// let text = "Introduction\n\nThe splitter reads\ntokens. Then it stops.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ', '\n']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// splitter.set_paragraph_breaks(true);
+    ///
+    /// assert_eq!(Some("Introduction"), splitter.next().unwrap());
+    /// assert_eq!(Break::Paragraph, splitter.last_break());
+    /// assert_eq!(Some("The splitter reads tokens."), splitter.next().unwrap());
+    /// assert_eq!(Some("Then it stops."), splitter.next().unwrap());
+    /// ```
+    pub fn set_paragraph_breaks(&mut self, enabled: bool) {
+        self.paragraphs = enabled;
+    }
+
+    /// Returns how many times a quote has been given up on because it ran
+    /// past the limit set with `set_max_quote_tokens`
+    pub fn quote_recoveries(&self) -> usize {
+        self.recoveries
+    }
+
+    /// Returns why the last sentence ended
+    ///
+    /// ```
+    /// use token::Break;
+    ///
+    /// let text = "Really? \"Yes, really\" she said";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(
+    ///     tokenizer, vec![".", "!", "?"], vec!["\""]
+    /// );
+    ///
+    /// splitter.next().unwrap();
+    /// assert_eq!(Break::Terminator("?"), splitter.last_break());
+    /// splitter.next().unwrap();
+    /// assert_eq!(Break::Quote("\""), splitter.last_break());
+    /// splitter.next().unwrap();
+    /// assert_eq!(Break::End, splitter.last_break());
+    /// ```
+    pub fn last_break(&self) -> Break<'a> {
+        self.last
+    }
+
+    /// Sets how sentences are normalized for their checksums, or turns the
+    /// checksums off. A checksum is the same for sentences that are the same
+    /// once normalized, in any run and on any platform, so it can tell
+    /// duplicates apart as they stream by, or serve as a stable ID.
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    /// use token::overlap::Normalization;
+    ///
+    /// let text = "Hello there. HELLO, there! Bye.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec![".", "!"], vec![]);
+    /// splitter.set_checksum(Some(Normalization::FULL));
+    ///
+    /// let mut seen = HashSet::new();
+    /// let mut unique = Vec::new();
+    /// while let Some(sentence) = splitter.next().unwrap() {
+    ///     let sentence = sentence.to_string();
+    ///     if seen.insert(splitter.checksum().unwrap()) {
+    ///         unique.push(sentence);
+    ///     }
+    /// }
+    /// assert_eq!(vec!["Hello there.", "Bye."], unique);
+    /// ```
+    pub fn set_checksum(&mut self, normalization: Option<Normalization>) {
+        self.checksum = normalization;
+    }
+
+    /// Returns the checksum of the last sentence, if checksums are on and
+    /// there was a sentence
+    pub fn checksum(&self) -> Option<u64> {
+        let normalization = self.checksum.filter(|_| !self.current.is_empty())?;
+        Some(normalization.checksum(&self.current))
+    }
+
+    /// Returns every rule of the splitter in the order it applies them, with
+    /// whether each is enabled and its parameters. A rule that looks for
+    /// strings does nothing without them, even when it is enabled.
+    ///
+    /// ```
+    /// use token::Rule;
+    ///
+    /// let tokenizer = token::Tokenizer::new("".as_bytes(), vec![' ']);
+    /// let splitter = token::SentenceSplitter::legal(tokenizer);
+    /// let rules = splitter.rules();
+    /// assert_eq!(Rule::ALL.len(), rules.len());
+    ///
+    /// let terminators = &rules[5];
+    /// assert_eq!("terminators", terminators.rule.name());
+    /// assert_eq!(vec![".", "!", "?"], terminators.strings);
+    /// let abbreviations = &rules[6];
+    /// assert!(abbreviations.enabled && abbreviations.strings.contains(&"U.S.C."));
+    /// assert!(splitter.is_rule_enabled(Rule::Initials));
+    /// assert!(!splitter.is_rule_enabled(Rule::LowercaseContinues));
+    /// ```
+    pub fn rules(&self) -> Vec<RuleInfo<'a>> {
+        Rule::ALL.iter().map(|&rule| {
+            let strings = match rule {
+                Rule::Quotes => self.quotes.clone(),
+                Rule::Terminators => self.terminators.clone(),
+                Rule::Abbreviations => self.abbreviations.clone(),
+                Rule::OrdinalDates => self.ordinal_months.clone(),
+                Rule::ClauseTerminators => self.clause_terminators.clone(),
+                _ => Vec::new(),
+            };
+            let max_tokens = if rule == Rule::Quotes { self.max_quote_tokens } else { None };
+            RuleInfo { rule, enabled: self.is_rule_enabled(rule), strings, max_tokens }
+        }).collect()
+    }
+
+    /// Returns whether the splitter applies a rule
+    pub fn is_rule_enabled(&self, rule: Rule) -> bool {
+        match rule {
+            Rule::Paragraphs => self.paragraphs,
+            Rule::Newlines => self.hard_newlines,
+            Rule::Initials => self.initials,
+            Rule::RomanNumerals => self.roman_numerals,
+            Rule::LowercaseContinues => self.lowercase_continues,
+            _ => !self.disabled.contains(&rule),
+        }
+    }
+
+    /// Turns a rule on or off, as for a toggle in an application. The rules
+    /// that have parameters keep them while they are off, and a rule without
+    /// them is the same as its own setter, such as `set_initials`.
+    ///
+    /// ```
+    /// use token::Rule;
+    ///
+    /// let text = "Wait... what? Dr. Who.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec![".", "?"], vec![]);
+    /// splitter.set_abbreviations(vec!["Dr."]);
+    /// splitter.set_rule_enabled(Rule::ThoughtTrails, false);
+    /// splitter.set_rule_enabled(Rule::Abbreviations, false);
+    ///
+    /// assert_eq!(Some("Wait..."), splitter.next().unwrap());
+    /// assert_eq!(Some("what?"), splitter.next().unwrap());
+    /// assert_eq!(Some("Dr."), splitter.next().unwrap());
+    /// assert_eq!(vec!["Dr."], splitter.rules()[6].strings);
+    /// ```
+    pub fn set_rule_enabled(&mut self, rule: Rule, enabled: bool) {
+        match rule {
+            Rule::Paragraphs => self.paragraphs = enabled,
+            Rule::Newlines => self.hard_newlines = enabled,
+            Rule::Initials => self.initials = enabled,
+            Rule::RomanNumerals => self.roman_numerals = enabled,
+            Rule::LowercaseContinues => self.lowercase_continues = enabled,
+            _ => {
+                self.disabled.retain(|&r| r != rule);
+                if !enabled {
+                    self.disabled.push(rule);
+                }
+            }
+        }
+    }
+
+    /// Returns the index of the record the last sentence was read from.
+    ///
+    /// When the tokenizer was created with `Tokenizer::records`, sentences
+    /// never span records, so each line is split as a separate document.
+    ///
+    /// ```
+    /// let text = "Dear sir. How are you\nFine, thanks!";
+    ///
+    /// let tokenizer = token::Tokenizer::records(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(
+    ///     tokenizer, vec![".", "!", "?"], vec![]
+    /// );
+    ///
+    /// assert_eq!(Some("Dear sir."), splitter.next().unwrap());
+    /// assert_eq!(Some("How are you"), splitter.next().unwrap());
+    /// assert_eq!(0, splitter.record());
+    /// assert_eq!(Some("Fine, thanks!"), splitter.next().unwrap());
+    /// assert_eq!(1, splitter.record());
+    /// ```
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    /// Checks that the terminators and quotes make sense together with the
+    /// separators of the tokenizer
+    ///
+    /// ```
+    /// use token::{ConfigError, SentenceSplitter, Tokenizer};
+    ///
+    /// let tokenizer = Tokenizer::new("".as_bytes(), vec![' ', '\n']);
+    /// let splitter = SentenceSplitter::new(tokenizer, vec![".", ".\n"], vec![]);
+    /// assert_eq!(Err(ConfigError::SeparatorInTerminator(".\n".to_string(), '\n')),
+    ///            splitter.validate());
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.tokenizer.validate()?;
+        let separators = &self.tokenizer.lookup;
+        for t in self.terminators.iter().chain(&self.clause_terminators) {
+            if t.is_empty() {
+                return Err(ConfigError::EmptyTerminator);
+            }
+            if let Some(c) = t.chars().find(|&c| separators.contains(c)) {
+                return Err(ConfigError::SeparatorInTerminator(t.to_string(), c));
+            }
+        }
+        let closing = self.closing_quotes.iter().map(|(_, closing)| closing);
+        for q in self.quotes.iter().chain(closing) {
+            if q.is_empty() {
+                return Err(ConfigError::EmptyQuote);
+            }
+            if let Some(c) = q.chars().find(|&c| separators.contains(c)) {
+                return Err(ConfigError::SeparatorInQuote(q.to_string(), c));
+            }
+            if self.terminators.contains(q) {
+                return Err(ConfigError::QuoteIsTerminator(q.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets limits on how much input is read and how much is buffered, for
+    /// both the splitter and its tokenizer
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.tokenizer.set_limits(limits);
+    }
+
+    /// Sets what the tokenizer does with input that is not valid UTF-8
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.tokenizer.set_invalid_utf8(policy);
+    }
+
+    /// Starts over on a new reader, keeping the configuration and the
+    /// allocated buffers of both the splitter and its tokenizer
+    ///
+    /// ```
+    /// let tokenizer = token::Tokenizer::new("One. Two.".as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    /// assert_eq!(Some("One."), splitter.next().unwrap());
+    ///
+    /// splitter.reset("Three. Four.".as_bytes());
+    /// assert_eq!(Some("Three."), splitter.next().unwrap());
+    /// assert_eq!(Some("Four."), splitter.next().unwrap());
+    /// assert_eq!(None, splitter.next().unwrap());
+    /// ```
+    pub fn reset(&mut self, reader: R) {
+        self.tokenizer.reset(reader);
+        self.current.clear();
+        self.record = 0;
+        self.held = false;
+        self.clause = None;
+        self.deferred = None;
+        self.looked_ahead = false;
+        self.last = Break::End;
+        self.bounds.clear();
+        self.spans.clear();
+        self.replay.clear();
+        self.recoveries = 0;
+        self.recovered = None;
+    }
+
+    /// Returns the next sentence
+    ///
+    /// Any per-call limits set with `set_limits` apply to the whole sentence,
+    /// not to each of its tokens.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use token::Limits;
+    ///
+    /// let text = "\"This quote never ends, and goes on and on";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec!["\""]);
+    /// splitter.set_limits(Limits {
+    ///     max_call_input: Some(20),
+    ///     max_call_time: Some(Duration::from_millis(100)),
+    ///     .. Limits::default()
+    /// });
+    /// assert!(splitter.next().is_err());
+    /// ```
+    #[allow(clippy::should_implement_trait)] // The sentence borrows the splitter
+    pub fn next(&mut self) -> io::Result<Option<&str>> {
+        self.tokenizer.begin_call();
+        self.current.clear();
+        self.bounds.clear();
+        self.spans.clear();
+        // The first token of a new record may be waiting from the last call
+        if self.held {
+            self.held = false;
+            self.record = self.tokenizer.record;
+        }
+        self.clause = None;
+        self.deferred = None;
+        self.looked_ahead = false;
+        let mut quote: &'a str = "";
+        let mut skip_quote = false;
+        loop {
+            // Tokens from a quote that was given up on are split again first
+            if let Some((token, span)) = self.replay.pop_front() {
+                let start = self.current.len();
+                self.current.push_str(&token);
+                if let Some(sentence_break) = self.pending_ends(start, span, false) {
+                    self.last = sentence_break;
+                    return Ok(Some(&self.current));
+                }
+                if let Some(sentence_break) = self.apply_rules(start, span, &mut quote, &mut skip_quote) {
+                    self.last = sentence_break;
+                    return Ok(Some(&self.current));
+                }
+                continue;
+            }
+
+            if self.scanning && quote.is_empty() && self.bounds.len() > 2 {
+                self.drop_scanned();
+            }
+
+            // Read the token straight into the sentence
+            let start = self.current.len();
+            if !self.tokenizer.read_token(&mut self.current)? {
+                self.last = self.deferred.take().map_or(Break::End, Break::Terminator);
+                if !self.current.is_empty() {
+                    self.current.pop(); // The space after the last token
+                    return Ok(Some(&self.current));
+                } else {
+                    return Ok(None);
+                }
+            }
+
+            // Sentences never continue into a new record
+            if self.tokenizer.record != self.record {
+                if start > 0 && self.is_rule_enabled(Rule::Records) {
+                    // Save the token for the next sentence
+                    let token = self.current[start..].to_string();
+                    self.replay.push_back((token, self.tokenizer.span));
+                    self.current.truncate(start - 1);
+                    self.held = true;
+                    self.last = Break::Record;
+                    return Ok(Some(&self.current));
+                }
+                self.record = self.tokenizer.record;
+            }
+            self.tokenizer.limits.check_buffered(self.current.len())?;
+            let newlines = self.tokenizer.gap.matches('\n').count();
+            let span = self.tokenizer.span;
+            if let Some(sentence_break) = self.pending_ends(start, span, newlines > 0) {
+                self.last = sentence_break;
                 return Ok(Some(&self.current));
             }
-            // SPAAAAAAAAACE
-            self.current.push_str(" ");
+            let line_break = if self.paragraphs && newlines > 1 {
+                Some(Break::Paragraph)
+            } else if self.hard_newlines && newlines > 0 {
+                Some(Break::Newline)
+            } else {
+                None
+            };
+            if let Some(sentence_break) = line_break.filter(|_| start > 0) {
+                // Save the token for the next sentence
+                self.replay.push_front((self.current[start..].to_string(), span));
+                self.current.truncate(start - 1);
+                self.last = sentence_break;
+                return Ok(Some(&self.current));
+            }
+            if let Some(sentence_break) = self.apply_rules(start, span, &mut quote, &mut skip_quote) {
+                self.last = sentence_break;
+                return Ok(Some(&self.current));
+            }
+        }
+    }
+
+    /// Returns the next sentence together with where it is in the input,
+    /// from the start of its first token to the end of its last
+    ///
+    /// ```
+    /// use token::Span;
+    ///
+    /// let text = "Hi there.\n  How are you?";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ', '\n']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec![".", "?"], vec![]);
+    ///
+    /// splitter.next().unwrap();
+    /// let (sentence, span) = splitter.next_with_span().unwrap().unwrap();
+    /// assert_eq!("How are you?", sentence);
+    /// assert_eq!(Span { byte_start: 12, byte_end: 24, line: 2, column: 3 }, span);
+    /// ```
+    pub fn next_with_span(&mut self) -> io::Result<Option<(&str, Span)>> {
+        if self.next()?.is_none() {
+            return Ok(None);
+        }
+        let mut span = self.spans[0];
+        span.byte_end = self.spans[self.spans.len() - 1].byte_end;
+        Ok(Some((&self.current, span)))
+    }
+
+    /// Returns where the next sentence is in the input, for when only the
+    /// boundaries of the sentences are needed. Only the last tokens of the
+    /// sentence are kept while it is read, or those of a quote in it, so a
+    /// long sentence doesn't have to be held in memory. Its text can be read
+    /// back later with an `extract::Extractor`, and `checksum` returns
+    /// `None` until the next sentence is read as text.
+    ///
+    /// ```
+    /// let text = "A long sentence goes on. Short.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+    ///
+    /// let span = splitter.next_span().unwrap().unwrap();
+    /// assert_eq!("A long sentence goes on.", &text[span.byte_start..span.byte_end]);
+    /// assert_eq!(Some("Short."), splitter.next().unwrap());
+    /// ```
+    pub fn next_span(&mut self) -> io::Result<Option<Span>> {
+        self.scanning = true;
+        let found = self.next().map(|sentence| sentence.is_some());
+        self.scanning = false;
+        if !found? {
+            return Ok(None);
+        }
+        let mut span = self.spans[0];
+        span.byte_end = self.spans[self.spans.len() - 1].byte_end;
+        self.current.clear();
+        self.bounds.clear();
+        Ok(Some(span))
+    }
+
+    /// Drops the text of all but the last token of the sentence, which the
+    /// rules may still look at, and the spans of all but the first and last
+    fn drop_scanned(&mut self) {
+        let (from, end) = self.bounds[self.bounds.len() - 1];
+        self.current.drain(..from);
+        self.bounds.clear();
+        // Keeps the bounds lined up with the spans
+        self.bounds.extend([(0, 0), (0, end - from)]);
+        let last = self.spans[self.spans.len() - 1];
+        self.spans.truncate(1);
+        self.spans.push(last);
+    }
+
+    /// Returns the next sentence as a handle that also gives access to its
+    /// tokens, without copying them
+    ///
+    /// ```
+    /// let text = "Hello there, world! Bye.";
+    /// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+    /// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["!", "."], vec![]);
+    ///
+    /// let sentence = splitter.next_sentence().unwrap().unwrap();
+    /// assert_eq!("Hello there, world!", sentence.text());
+    /// assert_eq!(3, sentence.len());
+    /// assert_eq!(vec!["Hello", "there,", "world!"], sentence.tokens().collect::<Vec<_>>());
+    /// ```
+    pub fn next_sentence(&mut self) -> io::Result<Option<Sentence<'_>>> {
+        if self.next()?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Sentence { text: &self.current, bounds: &self.bounds }))
+    }
+
+    /// Returns whether the next sentence is split as a new splitter would
+    /// split it from its first token, which is not the case while the tokens
+    /// of a quote that was given up on are split again
+    pub(crate) fn fresh(&self) -> bool {
+        self.replay.len() <= 1
+    }
+
+    /// Returns the offset just past the first token of the last sentence
+    #[cfg(feature = "codec")]
+    pub(crate) fn first_token_end(&self) -> usize {
+        self.spans[0].byte_end
+    }
+
+    /// Returns where the quote given up on last started, and where the token
+    /// that made it go past the limit ended, so that a quote that was only
+    /// given up on because the input was cut short can be split again
+    #[cfg(feature = "codec")]
+    pub(crate) fn recovered(&self) -> Option<(usize, usize)> {
+        self.recovered
+    }
+
+    /// Returns whether the end of the last sentence depended on the whole of
+    /// the token after it, so that it may have ended elsewhere if that token
+    /// went on
+    #[cfg(feature = "codec")]
+    pub(crate) fn looked_ahead(&self) -> bool {
+        self.looked_ahead
+    }
+
+    /// Ends the sentence before the token that was just added at `start`, if
+    /// the token before it ended in a clause terminator and the token starts
+    /// with an upper case letter or a new line, or if it ended in a deferred
+    /// terminator and the token doesn't start with a lower case letter (when
+    /// those continue the sentence) or name a month after an ordinal number
+    fn pending_ends(&mut self, start: usize, span: Span, newline: bool) -> Option<Break<'a>> {
+        let token = &self.current[start..];
+        let first = token.chars().next();
+        let (t, ends) = match self.deferred.take() {
+            Some(t) => {
+                let lowercase = self.lowercase_continues && first.is_some_and(char::is_lowercase);
+                let word = token.trim_end_matches(|c: char| !c.is_alphanumeric());
+                let ordinal = mem::take(&mut self.ordinal) && self.is_rule_enabled(Rule::OrdinalDates);
+                self.looked_ahead = ordinal && !newline && !lowercase;
+                (t, newline || !(lowercase || ordinal && self.ordinal_months.contains(&word)))
+            }
+            None => (self.clause.take()?, newline || first.is_some_and(char::is_uppercase)),
+        };
+        if !ends {
+            return None;
+        }
+        // Save the token for the next sentence
+        self.replay.push_front((token.to_string(), span));
+        self.current.truncate(start - 1);
+        Some(Break::Terminator(t))
+    }
+
+    /// Returns whether the token at `start`, which ends in the terminator
+    /// `t`, is an abbreviation, an initial or a Roman numeral that doesn't
+    /// end the sentence
+    fn abbreviated(&self, start: usize, t: &str) -> bool {
+        let token = self.current[start..].trim_start_matches(|c: char| !c.is_alphanumeric());
+        if self.abbreviations.contains(&token) && self.is_rule_enabled(Rule::Abbreviations) {
+            return true;
+        }
+        if !t.ends_with('.') {
+            return false;
+        }
+        // The token may be all terminator, with nothing left after trimming
+        let word = token.strip_suffix(t).unwrap_or("");
+        let mut chars = word.chars();
+        if self.initials && chars.next().is_some_and(char::is_uppercase) && chars.next().is_none() {
+            return true;
+        }
+        if self.roman_numerals && is_roman_numeral(word) {
+            // The token itself is the last of the bounds
+            let previous = self.bounds.len().checked_sub(2).map(|i| {
+                let (start, end) = self.bounds[i];
+                &self.current[start..end]
+            });
+            return previous.is_none_or(|p| p.chars().next().is_some_and(char::is_uppercase));
+        }
+        false
+    }
+
+    /// Returns whether the token at `start`, which ends in the terminator
+    /// `t`, is an ordinal number that may be followed by a month
+    fn ordinal_number(&self, start: usize, t: &str) -> bool {
+        let token = self.current[start..].trim_start_matches(|c: char| !c.is_alphanumeric());
+        let number = token.strip_suffix(t).unwrap_or("");
+        !self.ordinal_months.is_empty() && self.is_rule_enabled(Rule::OrdinalDates)
+            && t == "." && !number.is_empty()
+            && number.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Applies the splitting rules to the token that was just added to the
+    /// sentence at `start`, and returns why the sentence ends after it, if it
+    /// does
+    fn apply_rules(&mut self, start: usize, span: Span, quote: &mut &'a str, skip_quote: &mut bool)
+        -> Option<Break<'a>>
+    {
+        self.bounds.push((start, self.current.len()));
+        self.spans.push(span);
+
+        // Inside a quote
+        if !quote.is_empty() {
+            let closing = self.closing(quote);
+            if self.current[start..].ends_with(closing) {
+                return Some(Break::Quote(closing));
+            }
+            let quoted = self.bounds.len() - self.quote_from;
+            if self.max_quote_tokens.is_some_and(|max| quoted > max) {
+                // Give up on the quote, and split its tokens again without it,
+                // keeping where the first quote given up on at this token
+                // started
+                let from = self.spans[self.quote_from].byte_start;
+                self.recovered = match self.recovered {
+                    Some((start, end)) if end == span.byte_end => Some((start.min(from), end)),
+                    _ => Some((from, span.byte_end)),
+                };
+                let tokens = self.bounds[self.quote_from..].iter().zip(&self.spans[self.quote_from..]);
+                for (&(start, end), &span) in tokens {
+                    self.replay.push_back((self.current[start..end].to_string(), span));
+                }
+                self.current.truncate(self.bounds[self.quote_from].0);
+                self.bounds.truncate(self.quote_from);
+                self.spans.truncate(self.quote_from);
+                self.recoveries += 1;
+                *quote = "";
+                *skip_quote = true;
+                return None;
+            }
+            self.current.push(' ');
+            return None;
+        }
+
+        // Not inside a quote
+        // Check to see if a quote is starting
+        let opened = if *skip_quote {
+            *skip_quote = false;
+            None
+        } else {
+            let s = &self.current[start..];
+            self.quotes.iter().find(|q| s.starts_with(**q)).cloned()
+                .filter(|_| self.is_rule_enabled(Rule::Quotes))
+        };
+        if let Some(q) = opened {
+            let closing = self.closing(q);
+            if self.current[start..].ends_with(closing) { // It can end again
+                return Some(Break::Quote(closing));
+            }
+            *quote = q;
+            self.quote_from = self.bounds.len() - 1;
+            self.current.push(' ');
+            return None;
+        }
+
+        // Check whether the token is ending normally
+        // It ends in a terminating character
+        if self.current.ends_with("..") && self.is_rule_enabled(Rule::ThoughtTrails) {
+            // Continue thought trails
+            self.current.push(' ');
+            return None;
+        }
+        let terminator = {
+            let s = &self.current[start..];
+            self.terminators.iter().find(|t| s.ends_with(**t)).cloned()
+                .filter(|_| self.is_rule_enabled(Rule::Terminators))
+        };
+        if let Some(t) = terminator {
+            if !self.abbreviated(start, t) {
+                let ordinal = self.ordinal_number(start, t);
+                if !self.lowercase_continues && !ordinal {
+                    return Some(Break::Terminator(t));
+                }
+                // Whether it ends depends on the next token
+                self.deferred = Some(t);
+                self.ordinal = ordinal;
+                self.current.push(' ');
+                return None;
+            }
+        }
+        // It may end in a clause terminator, depending on the next token
+        let s = &self.current[start..];
+        self.clause = self.clause_terminators.iter().find(|t| s.ends_with(**t)).cloned()
+            .filter(|_| self.is_rule_enabled(Rule::ClauseTerminators));
+        // SPAAAAAAAAACE
+        self.current.push(' ');
+        None
+    }
+}
+
+/// A sentence returned by `SentenceSplitter::next_sentence`
+#[derive(Clone, Copy, Debug)]
+pub struct Sentence<'s> {
+    text: &'s str,
+    bounds: &'s [(usize, usize)],
+}
+
+impl <'s> Sentence<'s> {
+    /// Returns the text of the sentence, with its tokens separated by spaces
+    pub fn text(&self) -> &'s str {
+        self.text
+    }
+
+    /// Returns an iterator over the tokens of the sentence
+    pub fn tokens(&self) -> SentenceTokens<'s> {
+        SentenceTokens { text: self.text, bounds: self.bounds.iter() }
+    }
+
+    /// Returns the number of tokens in the sentence
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Returns whether the sentence has no tokens. This is never the case
+    /// for sentences returned by the splitter.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+}
+
+/// A sentence is serialized with its text and its tokens
+#[cfg(feature = "serde")]
+impl serde::Serialize for Sentence<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut sentence = serializer.serialize_struct("Sentence", 2)?;
+        sentence.serialize_field("text", self.text)?;
+        sentence.serialize_field("tokens", &self.tokens().collect::<Vec<_>>())?;
+        sentence.end()
+    }
+}
+
+/// An iterator over the tokens of a `Sentence`
+pub struct SentenceTokens<'s> {
+    text: &'s str,
+    bounds: core::slice::Iter<'s, (usize, usize)>,
+}
+
+impl <'s> Iterator for SentenceTokens<'s> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<&'s str> {
+        self.bounds.next().map(|&(start, end)| &self.text[start..end])
+    }
+}
+
+/// A structure for iteratively splitting a stream into records on arbitrary
+/// delimiter strings, such as form feeds or `"\n---\n"` lines
+#[cfg(feature = "std")]
+pub struct RecordSplitter<'a, R: Read> {
+    chars: Chars<BufReader<R>>,
+    delimiters: Vec<&'a str>,
+    buffer: String,
+    consumed: usize,
+    limits: Limits,
+}
+
+#[cfg(feature = "std")]
+impl <'a, R: Read> RecordSplitter<'a, R> {
+    /// Creates a new record-splitting iterator. Only one record (and the
+    /// delimiter after it) is held in memory at a time.
+    ///
+    /// When a delimiter is found, and it is also the start of a longer
+    /// delimiter, the longer one is used if it matches.
+    ///
+    /// ```
+    /// let text = "first\n---\nsecond\nstill second\n---\n\x0cthird";
+    ///
+    /// let mut splitter = token::RecordSplitter::new(
+    ///     text.as_bytes(), vec!["\n---\n", "\x0c"]
+    /// );
+    /// assert_eq!(Some("first"), splitter.next().unwrap());
+    /// assert_eq!(Some("second\nstill second"), splitter.next().unwrap());
+    /// assert_eq!(Some("third"), splitter.next().unwrap());
+    /// assert_eq!(None, splitter.next().unwrap());
+    /// ```
+    pub fn new(reader: R, delimiters: Vec<&'a str>) -> RecordSplitter<'a, R> {
+        RecordSplitter {
+            chars: Chars::new(BufReader::new(reader), InvalidUtf8::Error),
+            delimiters: delimiters.into_iter().filter(|d| !d.is_empty()).collect(),
+            buffer: String::new(),
+            consumed: 0,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Sets what to do with input that is not valid UTF-8. By default it is
+    /// an error.
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.chars.set_policy(policy);
+    }
+
+    /// Sets limits on how much input is read and how much of a record is
+    /// buffered, as with `Tokenizer::set_limits`. The limits on a single
+    /// call don't apply to records.
+    ///
+    /// ```
+    /// use token::{LimitExceeded, Limits};
+    ///
+    /// let text = "short\n---\na record that goes on and on";
+    /// let mut splitter = token::RecordSplitter::new(text.as_bytes(), vec!["\n---\n"]);
+    /// splitter.set_limits(Limits { max_buffered: Some(16), .. Limits::default() });
+    ///
+    /// assert_eq!(Some("short"), splitter.next().unwrap());
+    /// let e = splitter.next().unwrap_err();
+    /// let limit = e.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+    /// assert_eq!(Some(&LimitExceeded::Buffered(16)), limit);
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the next non-empty record
+    #[allow(clippy::should_implement_trait)] // The record borrows the splitter
+    pub fn next(&mut self) -> io::Result<Option<&str>> {
+        loop {
+            self.buffer.drain(..self.consumed);
+            self.consumed = 0;
+            // Where the record ends, and where the delimiter after it ends
+            let mut found: Option<(usize, usize)> = None;
+            loop {
+                if let Some((end, _)) = found {
+                    // Could a longer delimiter still match here?
+                    let rest = &self.buffer[end..];
+                    if !self.delimiters.iter().any(|d| d.len() > rest.len() && d.starts_with(rest)) {
+                        break;
+                    }
+                }
+                match self.chars.next() {
+                    Some(res) => self.buffer.push(res?),
+                    None => break,
+                }
+                self.limits.check_input(self.chars.offset())?;
+                self.limits.check_buffered(self.buffer.len())?;
+                for d in &self.delimiters {
+                    if self.buffer.ends_with(*d) {
+                        let start = self.buffer.len() - d.len();
+                        if found.is_none_or(|(end, _)| start <= end) {
+                            found = Some((start, self.buffer.len()));
+                        }
+                    }
+                }
+            }
+            let end = match found {
+                Some((end, after)) => {
+                    self.consumed = after;
+                    end
+                }
+                None => {
+                    // No more delimiters, so the rest is the last record
+                    if self.buffer.is_empty() {
+                        return Ok(None);
+                    }
+                    self.consumed = self.buffer.len();
+                    self.buffer.len()
+                }
+            };
+            if end > 0 {
+                return Ok(Some(&self.buffer[..end]));
+            }
         }
     }
 }
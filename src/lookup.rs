@@ -0,0 +1,170 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Typo-tolerant lookup of tokens, with a BK-tree built from token counts
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use token::lookup::BkTree;
+//!
+//! let mut counts = HashMap::new();
+//! let mut tokenizer = token::Tokenizer::new("the cat and the hat".as_bytes(), vec![' ']);
+//! while let Some(token) = tokenizer.next().unwrap() {
+//!     *counts.entry(token.to_string()).or_insert(0) += 1;
+//! }
+//!
+//! let tree = BkTree::from_counts(&counts);
+//! assert_eq!(Some("the"), tree.nearest("teh", 2).map(|m| m.token));
+//! assert_eq!(None, tree.nearest("dog", 1));
+//! ```
+
+use std::cmp;
+use std::collections::HashMap;
+
+/// Returns the Levenshtein distance between two strings: the number of
+/// chars that must be inserted, removed or replaced to turn one into the
+/// other
+///
+/// ```
+/// assert_eq!(3, token::lookup::distance("kitten", "sitting"));
+/// assert_eq!(1, token::lookup::distance("blåbær", "blåbær!"));
+/// ```
+pub fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..b.len() + 1).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let replaced = diagonal + if ca == cb { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = cmp::min(replaced, cmp::min(row[j], row[j + 1]) + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// A token found near the one looked up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match<'a> {
+    /// The token found
+    pub token: &'a str,
+    /// How many times the token was counted
+    pub count: usize,
+    /// The distance from the token looked up
+    pub distance: usize,
+}
+
+struct Node {
+    token: String,
+    count: usize,
+    children: Vec<(usize, usize)>,
+}
+
+/// An index of tokens for finding the ones within a given edit distance of
+/// a query, without comparing it with every token
+#[derive(Default)]
+pub struct BkTree {
+    nodes: Vec<Node>,
+}
+
+impl BkTree {
+    /// Creates a new, empty tree
+    pub fn new() -> BkTree {
+        BkTree::default()
+    }
+
+    /// Creates a tree of all the tokens in a frequency table
+    pub fn from_counts(counts: &HashMap<String, usize>) -> BkTree {
+        let mut tree = BkTree::new();
+        for (token, &count) in counts {
+            tree.insert(token, count);
+        }
+        tree
+    }
+
+    /// Adds a token with the given count, or adds to its count if it is
+    /// already in the tree
+    pub fn insert(&mut self, token: &str, count: usize) {
+        if self.nodes.is_empty() {
+            self.nodes.push(Node { token: token.to_string(), count, children: Vec::new() });
+            return;
+        }
+        let mut node = 0;
+        loop {
+            let d = distance(token, &self.nodes[node].token);
+            if d == 0 {
+                self.nodes[node].count += count;
+                return;
+            }
+            // Walk down the edge with the same distance, or add one
+            let next = self.nodes[node].children.iter().find(|child| child.0 == d).map(|child| child.1);
+            match next {
+                Some(child) => node = child,
+                None => {
+                    let index = self.nodes.len();
+                    self.nodes[node].children.push((d, index));
+                    self.nodes.push(Node { token: token.to_string(), count, children: Vec::new() });
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns the number of distinct tokens in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the tree has no tokens
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns every token within `max_distance` of the given one, closest
+    /// first, and the most frequent first among equally close tokens
+    ///
+    /// ```
+    /// let mut tree = token::lookup::BkTree::new();
+    /// tree.insert("book", 10);
+    /// tree.insert("books", 3);
+    /// tree.insert("boot", 5);
+    /// tree.insert("cake", 7);
+    ///
+    /// let found: Vec<_> = tree.find("bok", 1).iter().map(|m| m.token).collect();
+    /// assert_eq!(vec!["book"], found);
+    /// let found: Vec<_> = tree.find("boko", 2).iter().map(|m| m.token).collect();
+    /// assert_eq!(vec!["book", "boot", "books"], found);
+    /// ```
+    pub fn find(&self, token: &str, max_distance: usize) -> Vec<Match<'_>> {
+        let mut found = Vec::new();
+        if self.nodes.is_empty() {
+            return found;
+        }
+        let mut pending = vec![0];
+        while let Some(index) = pending.pop() {
+            let node = &self.nodes[index];
+            let d = distance(token, &node.token);
+            if d <= max_distance {
+                found.push(Match { token: &node.token, count: node.count, distance: d });
+            }
+            // By the triangle inequality, only children this far away can match
+            for &(edge, child) in &node.children {
+                if edge + max_distance >= d && edge <= d + max_distance {
+                    pending.push(child);
+                }
+            }
+        }
+        found.sort_by(|a, b| {
+            a.distance.cmp(&b.distance)
+                .then(b.count.cmp(&a.count))
+                .then(a.token.cmp(b.token))
+        });
+        found
+    }
+
+    /// Returns the closest, and then most frequent, token within
+    /// `max_distance` of the given one
+    pub fn nearest(&self, token: &str, max_distance: usize) -> Option<Match<'_>> {
+        self.find(token, max_distance).into_iter().next()
+    }
+}
@@ -0,0 +1,517 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! The `token` command, which splits files or standard input into tokens or
+//! sentences, one per line
+
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, IsTerminal, Read, Write};
+use std::process;
+
+use token::encoding::Utf16Writer;
+use token::stats::Statistics;
+use token::{SentenceSplitter, Span, Tokenizer};
+
+const USAGE: &str = "\
+Usage: token <tokens|sentences|stats> [options] [file...]
+
+Splits the files, or standard input if there are none (or for `-`), into
+tokens or sentences, and writes them one per line, or as set by --format.
+With `stats`, counts the tokens and sentences of all the files instead.
+
+Options:
+    --format <format>         How to write them (default: lines)
+    --separators <chars>      Chars that separate tokens (default: \" \\t\\r\\n\")
+    --terminators <list>      Comma-separated sentence terminators (default: \".,!,?\")
+    --quotes <list>           Comma-separated quotes (default: none)
+    --top <n>                 How many of the most frequent tokens `stats`
+                              lists (default: 10)
+    --error-format <format>   How to write errors: text, or json for a JSON
+                              object per line (default: text)
+    --output-encoding <name>  How to encode the output when it is not a
+                              terminal: utf-8, or utf-16 (little-endian, with
+                              a byte order mark) (default: utf-8)
+    -h, --help                Shows this message
+
+The escapes \\n, \\t, \\r, \\\\ and \\, can be used in the values of the options.
+
+Formats:
+    lines    The text of each
+    jsonl    A JSON object of the text and its byte offsets, line and column
+    tsv      The byte offsets, line, column and text, separated by tabs
+    nul      The text of each followed by a null byte, as for `xargs -0`
+
+On Windows, the * and ? wildcards in the last part of a file path are
+expanded, as shells elsewhere do, and the console is written to in UTF-16.
+
+A file that fails does not stop the others from being split.
+
+Exit codes:
+    0    Success
+    1    A file could not be read, or the output could not be written
+    2    The options are invalid
+    3    A file is not valid UTF-8
+    4    Some of the files failed, but not all of them (when all of them
+         failed, the code is that of the last one)";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Tokens,
+    Sentences,
+    Stats,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Lines,
+    JsonLines,
+    Tsv,
+    Nul,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "lines" => Ok(Format::Lines),
+            "jsonl" => Ok(Format::JsonLines),
+            "tsv" => Ok(Format::Tsv),
+            "nul" => Ok(Format::Nul),
+            _ => Err(format!("unknown format {}", name)),
+        }
+    }
+
+    /// Writes a token or sentence and where it is in its file
+    fn write(self, out: &mut impl Write, text: &str, span: Span) -> io::Result<()> {
+        match self {
+            Format::Lines => writeln!(out, "{}", text),
+            Format::JsonLines => {
+                write!(out, "{{\"text\":")?;
+                write_json_str(out, text)?;
+                writeln!(out, ",\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}",
+                    span.byte_start, span.byte_end, span.line, span.column)
+            }
+            Format::Tsv => {
+                write!(out, "{}\t{}\t{}\t{}\t", span.byte_start, span.byte_end, span.line, span.column)?;
+                // The text may have the tabs and newlines that delimit it
+                for c in text.chars() {
+                    match c {
+                        '\\' => write!(out, "\\\\")?,
+                        '\n' => write!(out, "\\n")?,
+                        '\r' => write!(out, "\\r")?,
+                        '\t' => write!(out, "\\t")?,
+                        c => write!(out, "{}", c)?,
+                    }
+                }
+                writeln!(out)
+            }
+            Format::Nul => write!(out, "{}\0", text),
+        }
+    }
+}
+
+/// Writes a string as a JSON string
+fn write_json_str(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    write!(out, "\"")
+}
+
+/// What went wrong, which decides the exit code
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    Io = 1,
+    Config = 2,
+    Decode = 3,
+    Partial = 4,
+}
+
+impl Failure {
+    /// Returns the kind of an error in reading a file. Input that is invalid
+    /// data can only be input that isn't UTF-8: the tokenizer also reports a
+    /// quote that is never closed as invalid data, but only for its own
+    /// quotes, which the command never sets (`--quotes` are those of the
+    /// splitter, which doesn't fail on them).
+    fn of(e: &io::Error) -> Failure {
+        match e.kind() {
+            io::ErrorKind::InvalidData => Failure::Decode,
+            io::ErrorKind::InvalidInput => Failure::Config,
+            _ => Failure::Io,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Failure::Io => "io",
+            Failure::Config => "config",
+            Failure::Decode => "decode",
+            Failure::Partial => "partial",
+        }
+    }
+}
+
+/// The options that take a value
+const VALUE_OPTIONS: [&str; 7] = [
+    "--format", "--separators", "--terminators", "--quotes", "--top", "--error-format",
+    "--output-encoding",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ErrorFormat {
+    Text,
+    Json,
+}
+
+impl ErrorFormat {
+    fn parse(name: &str) -> Result<ErrorFormat, String> {
+        match name {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("unknown error format {}", name)),
+        }
+    }
+
+    /// Returns the error format asked for. It is looked up before the rest
+    /// of the arguments are parsed, so that errors in any of them are written
+    /// in it too, and the values of the other options are skipped so that
+    /// none of them is taken for it.
+    fn of(args: &[String]) -> ErrorFormat {
+        let mut format = ErrorFormat::Text;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !VALUE_OPTIONS.contains(&arg.as_str()) {
+                continue;
+            }
+            match args.next() {
+                Some(value) if arg == "--error-format" => {
+                    format = ErrorFormat::parse(value).unwrap_or(format);
+                }
+                _ => {}
+            }
+        }
+        format
+    }
+
+    /// Writes an error to the standard error, with the file it happened in
+    fn report(self, failure: Failure, file: Option<&str>, message: &str) {
+        // There is nowhere left to tell about errors in writing errors
+        let _ = self.write(&mut io::stderr().lock(), failure, file, message);
+    }
+
+    fn write(self, err: &mut impl Write, failure: Failure, file: Option<&str>, message: &str)
+        -> io::Result<()>
+    {
+        if self == ErrorFormat::Text {
+            return match file {
+                Some(file) => writeln!(err, "token: {}: {}", file, message),
+                None => writeln!(err, "token: {}", message),
+            };
+        }
+        write!(err, "{{\"error\":\"{}\"", failure.name())?;
+        if let Some(file) = file {
+            write!(err, ",\"file\":")?;
+            write_json_str(err, file)?;
+        }
+        write!(err, ",\"message\":")?;
+        write_json_str(err, message)?;
+        writeln!(err, "}}")
+    }
+}
+
+/// Why a file could not be split
+enum FileError {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+struct Options {
+    command: Command,
+    format: Format,
+    separators: Vec<char>,
+    terminators: Vec<String>,
+    quotes: Vec<String>,
+    top: usize,
+    utf16: bool,
+    files: Vec<String>,
+}
+
+/// Replaces the escapes of an option value by the chars they stand for,
+/// keeping escaped commas apart from those that separate a list
+fn unescape(value: &str) -> Vec<(char, bool)> {
+    let mut chars = Vec::new();
+    let mut iter = value.chars();
+    while let Some(c) = iter.next() {
+        if c != '\\' {
+            chars.push((c, false));
+            continue;
+        }
+        match iter.next() {
+            Some('n') => chars.push(('\n', true)),
+            Some('t') => chars.push(('\t', true)),
+            Some('r') => chars.push(('\r', true)),
+            Some(c) => chars.push((c, true)),
+            None => chars.push(('\\', false)),
+        }
+    }
+    chars
+}
+
+/// Splits a comma-separated list, leaving out empty entries
+fn list(value: &str) -> Vec<String> {
+    let mut entries = vec![String::new()];
+    for (c, escaped) in unescape(value) {
+        if c == ',' && !escaped {
+            entries.push(String::new());
+        } else if let Some(entry) = entries.last_mut() {
+            entry.push(c);
+        }
+    }
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+/// Parses the arguments, or returns `None` if help was asked for
+fn parse(args: Vec<String>) -> Result<Option<Options>, String> {
+    let mut command = None;
+    let mut options = Options {
+        command: Command::Tokens,
+        format: Format::Lines,
+        separators: vec![' ', '\t', '\r', '\n'],
+        terminators: vec![".".to_string(), "!".to_string(), "?".to_string()],
+        quotes: Vec::new(),
+        top: 10,
+        utf16: false,
+        files: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--separators" => {
+                options.separators = unescape(&value(&arg)?).into_iter().map(|(c, _)| c).collect();
+            }
+            "--terminators" => options.terminators = list(&value(&arg)?),
+            "--quotes" => options.quotes = list(&value(&arg)?),
+            "--format" => options.format = Format::parse(&value(&arg)?)?,
+            // Looked up by `ErrorFormat::of`
+            "--error-format" => {
+                ErrorFormat::parse(&value(&arg)?)?;
+            }
+            "--output-encoding" => {
+                options.utf16 = match value(&arg)?.as_str() {
+                    "utf-8" => false,
+                    "utf-16" => true,
+                    name => return Err(format!("unknown output encoding {}", name)),
+                };
+            }
+            "--top" => {
+                let top = value(&arg)?;
+                options.top = top.parse().map_err(|_| format!("--top needs a number, not {}", top))?;
+            }
+            "-" => options.files.push(arg),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            "tokens" if command.is_none() => command = Some(Command::Tokens),
+            "sentences" if command.is_none() => command = Some(Command::Sentences),
+            "stats" if command.is_none() => command = Some(Command::Stats),
+            _ if command.is_none() => return Err(format!("unknown command {}", arg)),
+            _ => options.files.push(arg),
+        }
+    }
+    options.command = command.ok_or("no command given")?;
+    // The shells of Windows leave the wildcards to the programs
+    if cfg!(windows) {
+        options.files = options.files.iter().flat_map(|path| expand(path)).collect();
+    }
+    if options.files.is_empty() {
+        options.files.push("-".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Returns whether a file name matches a pattern of `*` and `?` wildcards,
+/// ignoring case as Windows does
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+        (Some(p), Some(c)) if p.to_lowercase().eq(c.to_lowercase()) => {
+            matches(&pattern[1..], &name[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Expands the wildcards in the last part of a path into the files they
+/// match, in order. A path that matches nothing is kept, so that it is
+/// reported as missing.
+fn expand(path: &str) -> Vec<String> {
+    let (dir, pattern) = match path.rfind(['/', '\\']) {
+        Some(i) => path.split_at(i + 1),
+        None => ("", path),
+    };
+    if !pattern.contains(['*', '?']) {
+        return vec![path.to_string()];
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let entries = match fs::read_dir(if dir.is_empty() { "." } else { dir }) {
+        Ok(entries) => entries,
+        Err(_) => return vec![path.to_string()],
+    };
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| matches(&pattern, &name.chars().collect::<Vec<_>>()))
+        .map(|name| format!("{}{}", dir, name))
+        .collect();
+    if paths.is_empty() {
+        paths.push(path.to_string());
+    }
+    paths.sort();
+    paths
+}
+
+fn open(path: &str) -> io::Result<Box<dyn Read>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin()))
+    } else {
+        File::open(path).map(|file| Box::new(file) as Box<dyn Read>)
+    }
+}
+
+/// Checks that the separators and terminators make sense, before any file
+/// is read
+fn validate(options: &Options) -> Result<(), String> {
+    let tokenizer = Tokenizer::new(io::empty(), options.separators.clone());
+    tokenizer.validate().map_err(|e| e.to_string())?;
+    if options.command != Command::Tokens {
+        let terminators: Vec<&str> = options.terminators.iter().map(String::as_str).collect();
+        let quotes: Vec<&str> = options.quotes.iter().map(String::as_str).collect();
+        SentenceSplitter::new(tokenizer, terminators, quotes).validate().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Splits a file, writing what it finds or counting it
+fn split(path: &str, options: &Options, out: &mut impl Write, statistics: &mut Statistics)
+    -> Result<(), FileError>
+{
+    let tokenizer = Tokenizer::new(open(path).map_err(FileError::Read)?, options.separators.clone());
+    if options.command == Command::Tokens {
+        let mut tokenizer = tokenizer;
+        while let Some((token, span)) = tokenizer.next_with_span().map_err(FileError::Read)? {
+            options.format.write(out, token, span).map_err(FileError::Write)?;
+        }
+        return Ok(());
+    }
+    let terminators: Vec<&str> = options.terminators.iter().map(String::as_str).collect();
+    let quotes: Vec<&str> = options.quotes.iter().map(String::as_str).collect();
+    let mut splitter = SentenceSplitter::new(tokenizer, terminators, quotes);
+    if options.command == Command::Stats {
+        return statistics.collect(&mut splitter).map_err(FileError::Read);
+    }
+    while let Some((sentence, span)) = splitter.next_with_span().map_err(FileError::Read)? {
+        options.format.write(out, sentence, span).map_err(FileError::Write)?;
+    }
+    Ok(())
+}
+
+fn write_statistics(out: &mut impl Write, statistics: &Statistics, top: usize) -> io::Result<()> {
+    writeln!(out, "tokens: {}", statistics.tokens())?;
+    writeln!(out, "sentences: {}", statistics.sentences())?;
+    writeln!(out, "unique tokens: {}", statistics.unique_tokens())?;
+    writeln!(out, "average sentence length: {:.2} tokens", statistics.average_sentence_len())?;
+    writeln!(out, "most frequent tokens:")?;
+    for (token, count) in statistics.top(top) {
+        writeln!(out, "{:>8} {}", count, token)?;
+    }
+    Ok(())
+}
+
+/// Splits the files, and returns the failure to exit with, if any
+fn run(options: &Options, errors: ErrorFormat) -> Option<Failure> {
+    let stdout = io::stdout();
+    // The standard library writes to the Windows console in UTF-16 by
+    // itself, and would refuse the bytes of the writer
+    let out: Box<dyn Write> = if options.utf16 && !stdout.is_terminal() {
+        Box::new(Utf16Writer::new(stdout.lock()))
+    } else {
+        Box::new(stdout.lock())
+    };
+    let mut out = BufWriter::new(out);
+    let mut statistics = Statistics::new();
+    let mut failures = Vec::new();
+    let mut written = Ok(());
+    for path in &options.files {
+        match split(path, options, &mut out, &mut statistics) {
+            Ok(()) => {}
+            Err(FileError::Read(e)) => {
+                let failure = Failure::of(&e);
+                errors.report(failure, Some(path), &e.to_string());
+                failures.push(failure);
+            }
+            Err(FileError::Write(e)) => {
+                written = Err(e);
+                break;
+            }
+        }
+    }
+    if written.is_ok() && options.command == Command::Stats {
+        written = write_statistics(&mut out, &statistics, options.top);
+    }
+    match written.and_then(|()| out.flush()) {
+        Ok(()) => {}
+        // The reader of the output has had enough, as with `head`, which
+        // doesn't hide the files that failed before
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            errors.report(Failure::Io, None, &format!("could not write the output: {}", e));
+            return Some(Failure::Io);
+        }
+    }
+    match failures.last() {
+        Some(_) if failures.len() < options.files.len() => Some(Failure::Partial),
+        last => last.cloned(),
+    }
+}
+
+/// Reports invalid options, with the usage for people, and exits
+fn invalid(errors: ErrorFormat, message: &str) -> ! {
+    match errors {
+        ErrorFormat::Text => eprintln!("token: {}\n\n{}", message, USAGE),
+        ErrorFormat::Json => errors.report(Failure::Config, None, message),
+    }
+    process::exit(Failure::Config as i32);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let errors = ErrorFormat::of(&args);
+    let options = match parse(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            let _ = writeln!(io::stdout(), "{}", USAGE);
+            return;
+        }
+        Err(message) => invalid(errors, &message),
+    };
+    if let Err(message) = validate(&options) {
+        invalid(errors, &message);
+    }
+    if let Some(failure) = run(&options, errors) {
+        process::exit(failure as i32);
+    }
+}
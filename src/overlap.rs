@@ -0,0 +1,198 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Normalized sentence hashing, for finding sentences shared between corpora
+//!
+//! ```
+//! use token::overlap::SentenceSet;
+//!
+//! let mut train = SentenceSet::new();
+//! train.insert("The cat sat on the mat.");
+//! train.insert("It was a sunny day!");
+//! train.insert("It was a sunny day!");
+//!
+//! let mut test = SentenceSet::new();
+//! test.insert("the cat  sat on the mat");
+//! test.insert("Nothing to see here.");
+//!
+//! let overlap = train.overlap(&test);
+//! assert_eq!(1, overlap.shared);
+//! assert_eq!(1, overlap.left);
+//! assert_eq!(1, overlap.right);
+//! ```
+
+use std::collections::HashMap;
+
+/// What is done to a sentence before it is hashed, so that sentences that
+/// only differ in what is done away with hash the same
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Normalization {
+    /// Whether letters are lowercased
+    pub lowercase: bool,
+    /// Whether everything but letters, digits and whitespace is removed
+    pub strip_punctuation: bool,
+    /// Whether whitespace runs are collapsed into single spaces, and
+    /// whitespace at the ends is removed
+    pub collapse_whitespace: bool,
+}
+
+impl Normalization {
+    /// Leaves sentences as they are, for exact duplicates only
+    pub const NONE: Normalization = Normalization {
+        lowercase: false,
+        strip_punctuation: false,
+        collapse_whitespace: false,
+    };
+
+    /// Does everything, as `normalize` does
+    pub const FULL: Normalization = Normalization {
+        lowercase: true,
+        strip_punctuation: true,
+        collapse_whitespace: true,
+    };
+
+    /// Returns the normalized form of a sentence
+    ///
+    /// ```
+    /// use token::overlap::Normalization;
+    ///
+    /// let normalization = Normalization { lowercase: true, .. Normalization::NONE };
+    /// assert_eq!("don't  panic!", normalization.apply("Don't  PANIC!"));
+    /// ```
+    pub fn apply(&self, sentence: &str) -> String {
+        let mut normalized = String::with_capacity(sentence.len());
+        let mut space = false;
+        for c in sentence.chars() {
+            if self.collapse_whitespace && c.is_whitespace() {
+                space = !normalized.is_empty();
+            } else if !self.strip_punctuation || c.is_alphanumeric() || c.is_whitespace() {
+                if space {
+                    normalized.push(' ');
+                    space = false;
+                }
+                if self.lowercase {
+                    normalized.extend(c.to_lowercase());
+                } else {
+                    normalized.push(c);
+                }
+            }
+        }
+        normalized
+    }
+
+    /// Returns a stable hash of the normalized form of a sentence
+    pub fn checksum(&self, sentence: &str) -> u64 {
+        fnv1a(&self.apply(sentence))
+    }
+}
+
+/// Normalizes everything, as sentences are compared by the sets
+impl Default for Normalization {
+    fn default() -> Normalization {
+        Normalization::FULL
+    }
+}
+
+/// Normalizes a sentence for comparison: letters are lowercased, everything
+/// but letters, digits and whitespace is removed, and whitespace runs are
+/// collapsed into single spaces
+///
+/// ```
+/// assert_eq!("dont panic", token::overlap::normalize("  Don't\tPANIC! "));
+/// ```
+pub fn normalize(sentence: &str) -> String {
+    Normalization::FULL.apply(sentence)
+}
+
+/// Hashes a string with 64-bit FNV-1a, which (unlike the hasher of the
+/// standard library) gives the same value across platforms and releases
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns a stable hash of the normalized form of a sentence
+///
+/// ```
+/// use token::overlap::sentence_hash;
+///
+/// assert_eq!(sentence_hash("Hello, world!"), sentence_hash("hello world"));
+/// ```
+pub fn sentence_hash(sentence: &str) -> u64 {
+    fnv1a(&normalize(sentence))
+}
+
+/// How much two sets of sentences overlap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overlap {
+    /// The number of distinct sentences found in both sets
+    pub shared: usize,
+    /// How many times the shared sentences occur in the left set
+    pub left: usize,
+    /// How many times the shared sentences occur in the right set
+    pub right: usize,
+}
+
+/// A multiset of normalized sentence hashes
+#[derive(Clone, Debug, Default)]
+pub struct SentenceSet {
+    counts: HashMap<u64, usize>,
+    total: usize,
+}
+
+impl SentenceSet {
+    /// Creates a new, empty set
+    pub fn new() -> SentenceSet {
+        SentenceSet::default()
+    }
+
+    /// Adds a sentence to the set, and returns how many times it has been
+    /// added now
+    pub fn insert(&mut self, sentence: &str) -> usize {
+        self.total += 1;
+        let count = self.counts.entry(sentence_hash(sentence)).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Returns how many times a sentence (or one that normalizes the same)
+    /// has been added
+    pub fn count(&self, sentence: &str) -> usize {
+        self.counts.get(&sentence_hash(sentence)).cloned().unwrap_or(0)
+    }
+
+    /// Returns whether a sentence (or one that normalizes the same) has been
+    /// added
+    pub fn contains(&self, sentence: &str) -> bool {
+        self.count(sentence) > 0
+    }
+
+    /// Returns the number of distinct sentences in the set
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns the number of sentences added to the set
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    /// Returns whether no sentences have been added
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Compares this set with another one
+    pub fn overlap(&self, other: &SentenceSet) -> Overlap {
+        let mut overlap = Overlap { shared: 0, left: 0, right: 0 };
+        for (hash, &count) in &self.counts {
+            if let Some(&other_count) = other.counts.get(hash) {
+                overlap.shared += 1;
+                overlap.left += count;
+                overlap.right += other_count;
+            }
+        }
+        overlap
+    }
+}
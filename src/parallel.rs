@@ -0,0 +1,291 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Sentence splitting of text in memory on several threads, with the same
+//! result as splitting it on one.
+//!
+//! The text is cut into chunks that are split at the same time, each from
+//! its start, as if nothing came before it. Since the splitter starts over at
+//! the start of a sentence (unless it is still splitting the tokens of a quote
+//! it gave up on again), the sentences of a chunk are the right ones from the
+//! first one that starts where a sentence of the whole text starts. The
+//! chunks are then joined in order: the sentences of each chunk are kept up
+//! to the first one that starts in the next chunk, and the next chunk is used
+//! from that same sentence on. When no sentence of the next chunk starts
+//! there, as when a sentence spans a whole chunk, the text is split again
+//! from the last sentence that started over until it meets a sentence of a
+//! later chunk.
+//!
+//! The splitters are made by a function, as for `token::codec`, and should
+//! not depend on anything that came before their input. Limits on how much
+//! is read apply to each chunk on its own.
+//!
+//! ```
+//! use token::parallel::ParallelSplitter;
+//!
+//! let mut splitter = ParallelSplitter::new(|input| {
+//!     let tokenizer = token::Tokenizer::new(input, vec![' ', '\n']);
+//!     token::SentenceSplitter::new(tokenizer, vec![".", "?"], vec![])
+//! });
+//! splitter.set_threads(3);
+//! splitter.set_min_chunk_len(1);
+//!
+//! let text = "Is this split? It is.\nAll of it, even\nthis.";
+//! let sentences: Vec<_> = splitter.split(text).unwrap().into_iter()
+//!     .map(|(sentence, span)| (sentence, span.line))
+//!     .collect();
+//! assert_eq!(vec![
+//!     ("Is this split?".to_string(), 1),
+//!     ("It is.".to_string(), 1),
+//!     ("All of it, even this.".to_string(), 2),
+//! ], sentences);
+//! ```
+
+use std::io;
+use std::num::NonZeroUsize;
+use std::panic;
+use std::thread;
+
+use crate::{SentenceSplitter, Span};
+
+/// The sentences a splitter found from some offset of the text, with spans
+/// from that offset, and whether the splitter was fresh at their start
+struct Run {
+    sentences: Vec<(String, Span, bool)>,
+    error: Option<io::Error>,
+}
+
+/// Where a run started in the text
+#[derive(Clone, Copy)]
+struct Origin {
+    byte: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Origin {
+    /// Returns where a span starts
+    fn of(span: Span) -> Origin {
+        Origin { byte: span.byte_start, line: span.line, column: span.column }
+    }
+
+    /// Moves a span of a run from this origin to where it is in the text
+    fn place(&self, span: Span) -> Span {
+        Span {
+            byte_start: self.byte + span.byte_start,
+            byte_end: self.byte + span.byte_end,
+            line: self.line + span.line - 1,
+            column: if span.line == 1 { self.column + span.column - 1 } else { span.column },
+        }
+    }
+}
+
+/// A splitter of text into sentences on several threads
+pub struct ParallelSplitter<F> {
+    make: F,
+    threads: usize,
+    min_chunk_len: usize,
+}
+
+impl<'a, F> ParallelSplitter<F>
+    where F: for<'b> Fn(&'b [u8]) -> SentenceSplitter<'a, &'b [u8]> + Sync
+{
+    /// Creates a new splitter, splitting with splitters made by `make`, on
+    /// as many threads as the machine can run at once
+    pub fn new(make: F) -> ParallelSplitter<F> {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        ParallelSplitter { make, threads, min_chunk_len: 64 * 1024 }
+    }
+
+    /// Sets how many threads to split on, at least one
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Sets how many bytes a chunk has at least, so that short texts aren't
+    /// cut into more chunks than they are worth. By default it is 64 KiB.
+    pub fn set_min_chunk_len(&mut self, len: usize) {
+        self.min_chunk_len = len.max(1);
+    }
+
+    /// Splits the text into sentences, returning them with their spans, as
+    /// `SentenceSplitter::next_with_span` would
+    ///
+    /// A sentence may span whole chunks, and a chunk may start inside a
+    /// quote, where its splitter can't know that it is in one. The text is
+    /// then split again from the start of the sentence the quote is in:
+    ///
+    /// ```
+    /// use token::parallel::ParallelSplitter;
+    ///
+    /// let mut splitter = ParallelSplitter::new(|input| {
+    ///     let tokenizer = token::Tokenizer::new(input, vec![' ']);
+    ///     token::SentenceSplitter::new(tokenizer, vec!["."], vec!["\""])
+    /// });
+    /// splitter.set_threads(4);
+    /// splitter.set_min_chunk_len(1);
+    ///
+    /// // The second of four chunks, "it. Go on now. Do ", is all inside the
+    /// // quote of the first sentence
+    /// let text = "He said \"Stop. Wait. Go on now. Do not look back.\" Then he left. Fine.";
+    /// let sentences: Vec<String> = splitter.split(text).unwrap().into_iter()
+    ///     .map(|(sentence, _)| sentence)
+    ///     .collect();
+    /// assert_eq!(vec![
+    ///     "He said \"Stop. Wait. Go on now. Do not look back.\"",
+    ///     "Then he left.",
+    ///     "Fine.",
+    /// ], sentences);
+    ///
+    /// // The second of three chunks starts inside the quote, at "it."
+    /// splitter.set_threads(3);
+    /// let text = "He said \"Stop. Wait. Go on now.\" Then he left. Fine.";
+    /// let sentences: Vec<_> = splitter.split(text).unwrap().into_iter()
+    ///     .map(|(sentence, span)| (sentence, span.byte_start))
+    ///     .collect();
+    /// assert_eq!(vec![
+    ///     ("He said \"Stop. Wait. Go on now.\"".to_string(), 0),
+    ///     ("Then he left.".to_string(), 33),
+    ///     ("Fine.".to_string(), 47),
+    /// ], sentences);
+    /// ```
+    pub fn split(&self, text: &str) -> io::Result<Vec<(String, Span)>> {
+        let cuts = self.cuts(text);
+        let mut runs: Vec<Run> = thread::scope(|scope| {
+            let workers: Vec<_> = cuts.windows(2)
+                .map(|chunk| scope.spawn(|| self.run(text, chunk[0], chunk[1])))
+                .collect();
+            workers.into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        });
+        let origins = origins(text, &cuts);
+        // The chunk that contains a byte offset
+        let chunk_of = |byte: usize| cuts.partition_point(|&cut| cut <= byte) - 1;
+        // The index of the sentence of a chunk that starts afresh at a byte
+        // offset
+        let find = |chunk: usize, byte: usize| {
+            let origin = origins[chunk];
+            runs[chunk].sentences.iter()
+                .position(|&(_, span, fresh)| fresh && origin.place(span).byte_start == byte)
+        };
+
+        let mut sentences = Vec::new();
+        let (mut chunk, mut from) = (0, 0);
+        // The start of the last sentence that was split afresh
+        let mut restart = origins[0];
+        loop {
+            // Keep the sentences of the chunk that start in it
+            let run = &runs[chunk];
+            let origin = origins[chunk];
+            let mut next = None;
+            for &(ref sentence, span, fresh) in &run.sentences[from..] {
+                let span = origin.place(span);
+                if span.byte_start >= cuts[chunk + 1] {
+                    next = Some((span, fresh));
+                    break;
+                }
+                if fresh {
+                    restart = Origin::of(span);
+                }
+                sentences.push((sentence.clone(), span));
+            }
+            let (next, fresh) = match next {
+                Some(next) => next,
+                None => match runs.swap_remove(chunk).error {
+                    Some(e) => return Err(e),
+                    None => return Ok(sentences),
+                },
+            };
+            // Go on with the chunk where the next sentence starts, if it has it
+            chunk = chunk_of(next.byte_start);
+            if let Some(index) = find(chunk, next.byte_start).filter(|_| fresh) {
+                from = index;
+                continue;
+            }
+            // Otherwise split again from the last fresh start until they meet
+            let mut splitter = (self.make)(&text.as_bytes()[restart.byte..]);
+            let origin = restart;
+            let mut met = false;
+            loop {
+                let fresh = splitter.fresh();
+                let (sentence, span) = match splitter.next_with_span()? {
+                    Some((sentence, span)) => (sentence, origin.place(span)),
+                    None => break,
+                };
+                if span.byte_start < next.byte_start {
+                    continue;
+                }
+                chunk = chunk_of(span.byte_start);
+                if let Some(index) = find(chunk, span.byte_start).filter(|_| fresh) {
+                    from = index;
+                    met = true;
+                    break;
+                }
+                if fresh {
+                    restart = Origin::of(span);
+                }
+                sentences.push((sentence.to_string(), span));
+            }
+            if !met {
+                return Ok(sentences);
+            }
+        }
+    }
+
+    /// Returns the offsets the text is cut into chunks at, from its start to
+    /// its end
+    fn cuts(&self, text: &str) -> Vec<usize> {
+        let chunks = self.threads.min(text.len() / self.min_chunk_len).max(1);
+        let mut cuts: Vec<usize> = (0..chunks).map(|i| {
+            let mut cut = i * text.len() / chunks;
+            while !text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            cut
+        }).collect();
+        cuts.dedup();
+        cuts.push(text.len());
+        cuts
+    }
+
+    /// Splits the text from the start of a chunk, up to and including the
+    /// first sentence that starts after it
+    fn run(&self, text: &str, start: usize, end: usize) -> Run {
+        let mut splitter = (self.make)(&text.as_bytes()[start..]);
+        let mut run = Run { sentences: Vec::new(), error: None };
+        loop {
+            let fresh = splitter.fresh();
+            match splitter.next_with_span() {
+                Ok(Some((sentence, span))) => {
+                    let after = span.byte_start >= end - start;
+                    run.sentences.push((sentence.to_string(), span, fresh));
+                    if after {
+                        return run;
+                    }
+                }
+                Ok(None) => return run,
+                Err(e) => {
+                    run.error = Some(e);
+                    return run;
+                }
+            }
+        }
+    }
+}
+
+/// Returns where each chunk starts in the text
+fn origins(text: &str, cuts: &[usize]) -> Vec<Origin> {
+    let mut origin = Origin { byte: 0, line: 1, column: 1 };
+    let mut origins = vec![origin];
+    for chunk in cuts.windows(2) {
+        let part = &text[chunk[0]..chunk[1]];
+        origin.byte = chunk[1];
+        origin.line += part.matches('\n').count();
+        origin.column = match part.rfind('\n') {
+            Some(i) => part[i + 1..].chars().count() + 1,
+            None => origin.column + part.chars().count(),
+        };
+        origins.push(origin);
+    }
+    origins
+}
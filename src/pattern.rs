@@ -0,0 +1,198 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Tokenizers with boundaries given by a regular expression, either as the
+//! separators between tokens or as the tokens themselves. This module needs
+//! the `regex` feature.
+//!
+//! ```
+//! use token::pattern::{Mode, Regex, StrRegexTokenizer};
+//!
+//! let text = "x1 = foo(42, y2);";
+//!
+//! let words = Regex::new(r"\w+").unwrap();
+//! let tokens: Vec<&str> = StrRegexTokenizer::new(text, words, Mode::Matches).collect();
+//! assert_eq!(vec!["x1", "foo", "42", "y2"], tokens);
+//!
+//! let commas = Regex::new(r",\s*").unwrap();
+//! let tokens: Vec<&str> = StrRegexTokenizer::new(text, commas, Mode::Split).collect();
+//! assert_eq!(vec!["x1 = foo(42", "y2);"], tokens);
+//! ```
+
+use std::io;
+use std::io::{BufReader, Read};
+
+pub use regex::Regex;
+
+use crate::decode::Chars;
+use crate::InvalidUtf8;
+
+/// What the matches of the regular expression are
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// The matches separate the tokens, as the separator chars of a
+    /// `Tokenizer` do
+    Split,
+    /// The matches are the tokens, and the text between them is left out
+    Matches,
+}
+
+/// Returns the bounds of the first non-empty match at or after `at`.
+/// Empty matches would give empty tokens, or split tokens anywhere.
+pub(crate) fn find(regex: &Regex, text: &str, mut at: usize) -> Option<(usize, usize)> {
+    while at <= text.len() {
+        let m = regex.find_at(text, at)?;
+        if m.start() < m.end() {
+            return Some((m.start(), m.end()));
+        }
+        at = m.end() + text[m.end()..].chars().next().map_or(1, |c| c.len_utf8());
+    }
+    None
+}
+
+/// A regex tokenizer for text that is already in memory, returning slices
+/// of the text
+pub struct StrRegexTokenizer<'a> {
+    text: &'a str,
+    pos: usize,
+    regex: Regex,
+    mode: Mode,
+}
+
+impl<'a> StrRegexTokenizer<'a> {
+    /// Creates a new tokenizer of the text
+    pub fn new(text: &'a str, regex: Regex, mode: Mode) -> StrRegexTokenizer<'a> {
+        StrRegexTokenizer { text, pos: 0, regex, mode }
+    }
+}
+
+impl<'a> Iterator for StrRegexTokenizer<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while self.pos < self.text.len() {
+            let (token, end) = match (find(&self.regex, self.text, self.pos), self.mode) {
+                (Some((start, end)), Mode::Split) => (&self.text[self.pos..start], end),
+                (Some((start, end)), Mode::Matches) => (&self.text[start..end], end),
+                (None, Mode::Split) => (&self.text[self.pos..], self.text.len()),
+                (None, Mode::Matches) => ("", self.text.len()),
+            };
+            self.pos = end;
+            if !token.is_empty() {
+                return Some(token);
+            }
+        }
+        None
+    }
+}
+
+/// A regex tokenizer for readers, which reads the input in chunks
+///
+/// A match is only trusted once the chunk size of input has been read past
+/// its end, or the input has ended, since more input could otherwise give
+/// an earlier or longer match. Matches longer than the chunk size may
+/// therefore be missed or cut short, and the chunk size should be set above
+/// the length of the longest expected match.
+///
+/// ```
+/// use token::pattern::{Mode, Regex, RegexTokenizer};
+///
+/// let source: &str = "2015-03-01 start\n2015-03-02 stop\n";
+///
+/// let dates = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+/// let mut tokenizer = RegexTokenizer::new(source.as_bytes(), dates, Mode::Matches);
+/// assert_eq!(Some("2015-03-01"), tokenizer.next().unwrap());
+/// assert_eq!(Some("2015-03-02"), tokenizer.next().unwrap());
+/// assert_eq!(None,               tokenizer.next().unwrap());
+/// ```
+pub struct RegexTokenizer<R: Read> {
+    chars: Chars<BufReader<R>>,
+    regex: Regex,
+    mode: Mode,
+    buffer: String,
+    pos: usize,
+    chunk_size: usize,
+    ended: bool,
+}
+
+impl<R: Read> RegexTokenizer<R> {
+    /// Creates a new tokenizer of a reader, with a chunk size of 64 KiB
+    pub fn new(reader: R, regex: Regex, mode: Mode) -> RegexTokenizer<R> {
+        RegexTokenizer {
+            chars: Chars::new(BufReader::new(reader), InvalidUtf8::Error),
+            regex,
+            mode,
+            buffer: String::new(),
+            pos: 0,
+            chunk_size: 64 * 1024,
+            ended: false,
+        }
+    }
+
+    /// Sets how many bytes are read past a match before it is trusted
+    pub fn set_chunk_size(&mut self, size: usize) {
+        self.chunk_size = size.max(1);
+    }
+
+    /// Sets what to do with input that is not valid UTF-8, as with
+    /// `Tokenizer::set_invalid_utf8`
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.chars.set_policy(policy);
+    }
+
+    /// Reads another chunk of input into the buffer, dropping what has
+    /// already been returned except for the last char, which matters to
+    /// assertions such as `\b`
+    fn fill(&mut self) -> io::Result<()> {
+        let keep = self.buffer[..self.pos].chars().next_back().map_or(0, |c| c.len_utf8());
+        self.buffer.drain(..self.pos - keep);
+        self.pos = keep;
+        let target = self.buffer.len() + self.chunk_size;
+        while self.buffer.len() < target {
+            match self.chars.next().transpose()? {
+                Some(c) => self.buffer.push(c),
+                None => {
+                    self.ended = true;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the next non-empty token
+    #[allow(clippy::should_implement_trait)] // The token borrows the tokenizer
+    pub fn next(&mut self) -> io::Result<Option<&str>> {
+        loop {
+            let found = find(&self.regex, &self.buffer, self.pos);
+            let trusted = match found {
+                Some((_, end)) => self.ended || end + self.chunk_size <= self.buffer.len(),
+                None => self.ended,
+            };
+            if !trusted {
+                if self.mode == Mode::Matches && found.is_none() {
+                    // A later match could not start this far back
+                    let mut skip = self.buffer.len().saturating_sub(self.chunk_size).max(self.pos);
+                    while !self.buffer.is_char_boundary(skip) {
+                        skip += 1;
+                    }
+                    self.pos = skip;
+                }
+                self.fill()?;
+                continue;
+            }
+            let (start, end, next) = match (found, self.mode) {
+                (Some((start, end)), Mode::Split) => (self.pos, start, end),
+                (Some((start, end)), Mode::Matches) => (start, end, end),
+                (None, Mode::Split) => (self.pos, self.buffer.len(), self.buffer.len()),
+                (None, Mode::Matches) => return Ok(None),
+            };
+            self.pos = next;
+            if start < end {
+                return Ok(Some(&self.buffer[start..end]));
+            }
+            if found.is_none() {
+                return Ok(None);
+            }
+        }
+    }
+}
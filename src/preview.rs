@@ -0,0 +1,124 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Short, printable previews of tokens and sentences, with their indices and
+//! spans, for debugging dumps
+//!
+//! Control chars are escaped so that every item stays on one line, and long
+//! items are cut to a given width in chars.
+//!
+//! ```
+//! let text = "Hello\tworld.\nA much longer second sentence.";
+//! let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ', '\n']);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+//!
+//! let mut dump = Vec::new();
+//! token::preview::write_sentences(&mut splitter, &mut dump, 20).unwrap();
+//! let dump = String::from_utf8(dump).unwrap();
+//! let lines: Vec<&str> = dump.lines().collect();
+//! assert_eq!("    0 1:1       0..12     Hello\\tworld.", lines[0]);
+//! assert_eq!("    1 2:1       13..43    A much longer secon…", lines[1]);
+//! ```
+
+use std::io;
+use std::io::{Read, Write};
+
+use crate::{SentenceSplitter, Span, Tokenizer};
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        '\\' => out.push_str("\\\\"),
+        c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        c => out.push(c),
+    }
+}
+
+/// Escapes backslashes and control chars, such as newlines, so that the
+/// text can be printed on one line
+///
+/// ```
+/// assert_eq!("a\\tb\\n\\u{7}", token::preview::escape("a\tb\n\x07"));
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+/// Escapes the text and cuts it to at most `width` chars, ending in `…` if
+/// anything was cut. Escape sequences are never cut in half.
+///
+/// ```
+/// use token::preview::preview;
+///
+/// assert_eq!("short", preview("short", 10));
+/// assert_eq!("a very l…", preview("a very long token", 9));
+/// assert_eq!("ab…", preview("ab\ncd", 4));
+/// ```
+pub fn preview(text: &str, width: usize) -> String {
+    let escaped = escape(text);
+    if escaped.chars().count() <= width {
+        return escaped;
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    let mut piece = String::new();
+    for c in text.chars() {
+        piece.clear();
+        push_escaped(&mut piece, c);
+        let len = piece.chars().count();
+        // Leave room for the ellipsis
+        if used + len >= width {
+            break;
+        }
+        out.push_str(&piece);
+        used += len;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+/// Formats one line of a dump: the index of the item, the line and column
+/// it starts at, its bytes in the input and a preview of its text
+///
+/// ```
+/// use token::Span;
+///
+/// let span = Span { byte_start: 4, byte_end: 9, line: 1, column: 5 };
+/// assert_eq!("    2 1:5       4..9      world", token::preview::line(2, "world", span, 20));
+/// ```
+pub fn line(index: usize, text: &str, span: Span, width: usize) -> String {
+    let position = format!("{}:{}", span.line, span.column);
+    let bytes = format!("{}..{}", span.byte_start, span.byte_end);
+    format!("{:>5} {:<9} {:<9} {}", index, position, bytes, preview(text, width))
+}
+
+/// Writes a line for each of the remaining tokens of a tokenizer
+pub fn write_tokens<R: Read, W: Write>(tokenizer: &mut Tokenizer<R>, out: &mut W, width: usize)
+    -> io::Result<()>
+{
+    let mut index = 0;
+    while let Some((token, span)) = tokenizer.next_with_span()? {
+        writeln!(out, "{}", line(index, token, span, width))?;
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Writes a line for each of the remaining sentences of a splitter
+pub fn write_sentences<R: Read, W: Write>(splitter: &mut SentenceSplitter<R>, out: &mut W,
+                                          width: usize) -> io::Result<()>
+{
+    let mut index = 0;
+    while let Some((sentence, span)) = splitter.next_with_span()? {
+        writeln!(out, "{}", line(index, sentence, span, width))?;
+        index += 1;
+    }
+    Ok(())
+}
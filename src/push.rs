@@ -0,0 +1,179 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! A tokenizer that is given its input a chunk at a time, instead of reading
+//! it, so that it can be driven by an event loop, a decompressor or a network
+//! stack. The tokens of each chunk are returned as soon as they are known to
+//! be whole, and the last one when the input is finished.
+//!
+//! ```
+//! use token::push::TokenizerState;
+//!
+//! let mut state = TokenizerState::new(vec![' ', '\n']);
+//! let tokens: Vec<_> = state.push_bytes(b"Hello wor").unwrap().collect();
+//! assert_eq!(vec!["Hello"], tokens);
+//! let tokens: Vec<_> = state.push_bytes(b"ld\nHow do").unwrap().collect();
+//! assert_eq!(vec!["world", "How"], tokens);
+//! assert_eq!(Some("do"), state.finish().unwrap());
+//! ```
+
+use std::io;
+use std::str;
+
+use crate::{InvalidUtf8, Limits, SeparatorSet};
+
+/// The state of a tokenizer that is pushed its input, separating tokens on
+/// chars like `Tokenizer::new`
+pub struct TokenizerState {
+    pub(crate) lookup: SeparatorSet,
+    invalid_utf8: InvalidUtf8,
+    /// Decoded input that has not been returned yet, from `pos` on
+    pub(crate) text: String,
+    pub(crate) pos: usize,
+    /// Input that could not be decoded yet, as it ends in part of a char
+    bytes: Vec<u8>,
+    /// How many bytes of the input have been decoded
+    offset: u64,
+    pub(crate) limits: Limits,
+}
+
+impl TokenizerState {
+    /// Creates a new tokenizer state from a set of separating chars
+    pub fn new(separators: Vec<char>) -> TokenizerState {
+        TokenizerState {
+            lookup: SeparatorSet::new(&separators),
+            invalid_utf8: InvalidUtf8::Error,
+            text: String::new(),
+            pos: 0,
+            bytes: Vec::new(),
+            offset: 0,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Sets what to do with input that is not valid UTF-8, as with
+    /// `Tokenizer::set_invalid_utf8`. A char that is split between two chunks
+    /// is not invalid.
+    ///
+    /// ```
+    /// use token::InvalidUtf8;
+    /// use token::push::TokenizerState;
+    ///
+    /// let mut state = TokenizerState::new(vec![' ']);
+    /// state.set_invalid_utf8(InvalidUtf8::Replace);
+    /// assert_eq!(0, state.push_bytes(b"caf\xc3").unwrap().count());
+    /// let tokens: Vec<_> = state.push_bytes(b"\xa9 \xff ").unwrap().collect();
+    /// assert_eq!(vec!["café", "\u{FFFD}"], tokens);
+    /// ```
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.invalid_utf8 = policy;
+    }
+
+    /// Sets limits on how much input is pushed before it is finished, and on
+    /// how much of it is kept until the next chunk, as with
+    /// `Tokenizer::set_limits`. The limits on a single call don't apply.
+    ///
+    /// ```
+    /// use token::{LimitExceeded, Limits};
+    /// use token::push::TokenizerState;
+    ///
+    /// let mut state = TokenizerState::new(vec![' ']);
+    /// state.set_limits(Limits { max_buffered: Some(8), .. Limits::default() });
+    /// assert_eq!(1, state.push_bytes(b"ok avery").unwrap().count());
+    /// let e = state.push_bytes(b"longtoken").err().unwrap();
+    /// let limit = e.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+    /// assert_eq!(Some(&LimitExceeded::Buffered(8)), limit);
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Adds a chunk of input, and returns the tokens that are known to be
+    /// whole. The token at the end of the chunk is kept until a separator
+    /// follows it, or the input is finished.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> io::Result<PushedTokens<'_>> {
+        self.feed(bytes, false)?;
+        let end = self.text.char_indices().rev()
+            .find(|&(_, c)| self.lookup.contains(c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        self.limits.check_buffered(self.text.len() - end + self.bytes.len())?;
+        self.pos = end;
+        Ok(PushedTokens { rest: &self.text[..end], lookup: &self.lookup })
+    }
+
+    /// Ends the input, and returns the token at the end of it, if there is
+    /// one. The state can then be pushed a new input.
+    pub fn finish(&mut self) -> io::Result<Option<&str>> {
+        self.feed(&[], true)?;
+        self.offset = 0;
+        let start = self.pos;
+        self.pos = self.text.len();
+        match &self.text[start..] {
+            "" => Ok(None),
+            // The separators before it have been returned already
+            token => Ok(Some(token)),
+        }
+    }
+
+    /// Drops the text that has been returned, and decodes what it can of the
+    /// bytes, together with those left over from before. At the end of the
+    /// input, a char that is still incomplete is invalid.
+    pub(crate) fn feed(&mut self, bytes: &[u8], ended: bool) -> io::Result<()> {
+        self.text.drain(..self.pos);
+        self.pos = 0;
+        self.bytes.extend_from_slice(bytes);
+        self.limits.check_input(self.offset + self.bytes.len() as u64)?;
+        let mut used = 0;
+        while used < self.bytes.len() {
+            match str::from_utf8(&self.bytes[used..]) {
+                Ok(valid) => {
+                    self.text.push_str(valid);
+                    used = self.bytes.len();
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    // Checked by `from_utf8` already
+                    self.text.push_str(str::from_utf8(&self.bytes[used..used + valid]).unwrap_or(""));
+                    used += valid;
+                    let len = match e.error_len() {
+                        Some(len) => len,
+                        // The last char is incomplete, and may be completed
+                        None if !ended => break,
+                        None => self.bytes.len() - used,
+                    };
+                    match self.invalid_utf8 {
+                        InvalidUtf8::Error => {
+                            let message = format!("invalid UTF-8 at byte {}", self.offset + used as u64);
+                            self.bytes.clear();
+                            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                        }
+                        InvalidUtf8::Replace => self.text.push(char::REPLACEMENT_CHARACTER),
+                        InvalidUtf8::Skip => {}
+                    }
+                    used += len;
+                }
+            }
+        }
+        self.bytes.drain(..used);
+        self.offset += used as u64;
+        Ok(())
+    }
+}
+
+/// An iterator over the tokens returned by `TokenizerState::push_bytes`
+pub struct PushedTokens<'s> {
+    rest: &'s str,
+    lookup: &'s SeparatorSet,
+}
+
+impl<'s> Iterator for PushedTokens<'s> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<&'s str> {
+        let start = self.rest.char_indices().find(|&(_, c)| !self.lookup.contains(c))?.0;
+        let rest = &self.rest[start..];
+        // Every pushed token is followed by a separator
+        let end = rest.char_indices().find(|&(_, c)| self.lookup.contains(c)).map_or(rest.len(), |(i, _)| i);
+        self.rest = &rest[end..];
+        Some(&rest[..end])
+    }
+}
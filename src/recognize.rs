@@ -0,0 +1,197 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Recognizers that claim parts of a text as tokens of their own before it is
+//! split on separators, for things like URLs, IDs and ticket numbers that
+//! would otherwise be split apart or stuck to the punctuation around them
+//!
+//! ```
+//! use token::recognize::Registry;
+//!
+//! let mut registry = Registry::new();
+//! registry.register("ticket", 0, |text: &str| {
+//!     text.match_indices("#").map(|(i, _)| {
+//!         let digits = text[i + 1..].bytes().take_while(u8::is_ascii_digit).count();
+//!         i..i + 1 + digits
+//!     }).filter(|claim| claim.len() > 1).collect()
+//! });
+//!
+//! let tokens = registry.tokenize("Fixed in (#1234), see #99.", vec![' ']);
+//! let texts: Vec<&str> = tokens.iter().map(|token| token.text).collect();
+//! assert_eq!(vec!["Fixed", "in", "(", "#1234", "),", "see", "#99", "."], texts);
+//! assert_eq!(Some("ticket"), tokens[3].recognizer);
+//! assert_eq!(None, tokens[4].recognizer);
+//! ```
+
+use std::cmp::Reverse;
+use std::ops::Range;
+
+#[cfg(feature = "regex")]
+use crate::pattern::{self, Regex};
+use crate::StrTokenizer;
+
+/// A token of a text tokenized by a `Registry`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecognizedToken<'t, 'r> {
+    /// The text of the token
+    pub text: &'t str,
+    /// The offset of its first byte in the text
+    pub byte_start: usize,
+    /// The offset just past its last byte in the text
+    pub byte_end: usize,
+    /// The name of the recognizer that claimed it, or `None` if it came from
+    /// splitting the rest of the text on separators
+    pub recognizer: Option<&'r str>,
+}
+
+type Claims = Box<dyn Fn(&str) -> Vec<Range<usize>> + Send + Sync>;
+
+struct Recognizer {
+    name: String,
+    priority: i32,
+    claims: Claims,
+}
+
+/// A set of named recognizers, each finding the parts of a text it claims
+///
+/// When claims overlap, the one of the recognizer with the highest priority
+/// wins, and between claims of the same priority, the one that starts first,
+/// then the longest, then that of the recognizer registered first. The claims
+/// that lost are dropped whole, and the text between the claims that are kept
+/// is split on the separators as by a `StrTokenizer`.
+#[derive(Default)]
+pub struct Registry {
+    recognizers: Vec<Recognizer>,
+}
+
+impl Registry {
+    /// Creates a new registry without recognizers
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Adds a recognizer, which returns the byte ranges of the text it
+    /// claims. Empty ranges, and ranges that aren't on char boundaries of
+    /// the text, are ignored. A recognizer registered under the name of
+    /// another one replaces it.
+    pub fn register<F>(&mut self, name: &str, priority: i32, claims: F)
+        where F: Fn(&str) -> Vec<Range<usize>> + Send + Sync + 'static
+    {
+        self.remove(name);
+        self.recognizers.push(Recognizer { name: name.to_string(), priority, claims: Box::new(claims) });
+    }
+
+    /// Adds a recognizer that claims the matches of a regular expression,
+    /// leaving out empty ones. This needs the `regex` feature.
+    ///
+    /// ```
+    /// use token::pattern::Regex;
+    /// use token::recognize::Registry;
+    ///
+    /// let mut registry = Registry::new();
+    /// registry.register_regex("url", 1, Regex::new(r"https?://[^\s)]+[^\s).,]").unwrap());
+    /// registry.register_regex("id", 0, Regex::new(r"[A-Z]+-[0-9]+").unwrap());
+    ///
+    /// let text = "See http://example.com/ABC-12. Also ABC-34!";
+    /// let tokens: Vec<_> = registry.tokenize(text, vec![' ']).iter()
+    ///     .map(|token| (token.text, token.recognizer))
+    ///     .collect();
+    /// assert_eq!(vec![
+    ///     ("See", None),
+    ///     ("http://example.com/ABC-12", Some("url")),
+    ///     (".", None),
+    ///     ("Also", None),
+    ///     ("ABC-34", Some("id")),
+    ///     ("!", None),
+    /// ], tokens);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn register_regex(&mut self, name: &str, priority: i32, regex: Regex) {
+        self.register(name, priority, move |text: &str| {
+            let mut claims = Vec::new();
+            let mut at = 0;
+            while let Some((start, end)) = pattern::find(&regex, text, at) {
+                claims.push(start..end);
+                at = end;
+            }
+            claims
+        });
+    }
+
+    /// Removes the recognizer with the given name, returning whether there
+    /// was one
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.recognizers.len();
+        self.recognizers.retain(|recognizer| recognizer.name != name);
+        self.recognizers.len() < len
+    }
+
+    /// Returns the names of the recognizers, from the highest priority to
+    /// the lowest
+    pub fn names(&self) -> Vec<&str> {
+        let mut recognizers: Vec<&Recognizer> = self.recognizers.iter().collect();
+        recognizers.sort_by_key(|recognizer| Reverse(recognizer.priority));
+        recognizers.iter().map(|recognizer| recognizer.name.as_str()).collect()
+    }
+
+    /// Returns the claims that are kept, in the order of the text, each with
+    /// the index of its recognizer
+    fn claims(&self, text: &str) -> Vec<(Range<usize>, usize)> {
+        let mut claims: Vec<(Range<usize>, usize)> = Vec::new();
+        for (index, recognizer) in self.recognizers.iter().enumerate() {
+            claims.extend((recognizer.claims)(text).into_iter()
+                .filter(|claim| {
+                    claim.start < claim.end && claim.end <= text.len()
+                        && text.is_char_boundary(claim.start) && text.is_char_boundary(claim.end)
+                })
+                .map(|claim| (claim, index)));
+        }
+        claims.sort_by(|(a, i), (b, j)| {
+            self.recognizers[*j].priority.cmp(&self.recognizers[*i].priority)
+                .then(a.start.cmp(&b.start))
+                .then(b.end.cmp(&a.end))
+                .then(i.cmp(j))
+        });
+        let mut kept: Vec<(Range<usize>, usize)> = Vec::new();
+        for (claim, index) in claims {
+            // The kept claims are in order, so only the neighbours can overlap
+            let at = kept.partition_point(|(kept, _)| kept.start < claim.start);
+            let before = at > 0 && kept[at - 1].0.end > claim.start;
+            let after = at < kept.len() && kept[at].0.start < claim.end;
+            if !before && !after {
+                kept.insert(at, (claim, index));
+            }
+        }
+        kept
+    }
+
+    /// Tokenizes a text, with the claims of the recognizers as tokens and the
+    /// rest of the text split on the separators
+    pub fn tokenize<'t>(&self, text: &'t str, separators: Vec<char>) -> Vec<RecognizedToken<'t, '_>> {
+        let mut tokens = Vec::new();
+        let split = |start: usize, end: usize, tokens: &mut Vec<RecognizedToken<'t, '_>>| {
+            let mut tokenizer = StrTokenizer::new(&text[start..end], separators.clone());
+            while let Some(token) = tokenizer.next() {
+                let byte_end = start + tokenizer.offset();
+                tokens.push(RecognizedToken {
+                    text: token,
+                    byte_start: byte_end - token.len(),
+                    byte_end,
+                    recognizer: None,
+                });
+            }
+        };
+        let mut pos = 0;
+        for (claim, index) in self.claims(text) {
+            split(pos, claim.start, &mut tokens);
+            tokens.push(RecognizedToken {
+                text: &text[claim.clone()],
+                byte_start: claim.start,
+                byte_end: claim.end,
+                recognizer: Some(&self.recognizers[index].name),
+            });
+            pos = claim.end;
+        }
+        split(pos, text.len(), &mut tokens);
+        tokens
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Tokenizing an edited text again while keeping track of the tokens that
+//! didn't change, for annotation tools that give tokens IDs
+//!
+//! ```
+//! use token::resegment::{resegment, Edit};
+//!
+//! let old = ["The", "cat", "sat", "on", "the", "mat."];
+//! let edits = resegment(&old, "The black cat sat on a mat.", vec![' ']);
+//! assert_eq!(vec![
+//!     Edit::Preserved(0, "The"),
+//!     Edit::Added("black"),
+//!     Edit::Preserved(1, "cat"),
+//!     Edit::Preserved(2, "sat"),
+//!     Edit::Preserved(3, "on"),
+//!     Edit::Removed(4),
+//!     Edit::Added("a"),
+//!     Edit::Preserved(5, "mat."),
+//! ], edits);
+//! ```
+
+use crate::StrTokenizer;
+
+/// What became of a token when the text was edited
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit<'t> {
+    /// The old token at this index is in the new text too
+    Preserved(usize, &'t str),
+    /// The old token at this index is not in the new text
+    Removed(usize),
+    /// This token of the new text is not among the old tokens
+    Added(&'t str),
+}
+
+/// Tokenizes the new text on the separators, and lines its tokens up with
+/// the old ones, keeping as many of them as can be kept in order. The edits
+/// are in the order of the text, with the tokens that were removed from a
+/// place before those that were added to it.
+pub fn resegment<'t, S: AsRef<str>>(old_tokens: &[S], new_text: &'t str, separators: Vec<char>)
+    -> Vec<Edit<'t>>
+{
+    let new: Vec<&str> = StrTokenizer::new(new_text, separators).collect();
+    let old: Vec<&str> = old_tokens.iter().map(AsRef::as_ref).collect();
+
+    // Edits are mostly local, so the tokens before and after them are kept
+    // without comparing everything with everything
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    // The length of the longest common subsequence of the rest of each
+    let width = b.len() + 1;
+    let mut lengths = vec![0; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Preserved(i, new[i])).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            edits.push(Edit::Preserved(prefix + i, b[j]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]) {
+            edits.push(Edit::Removed(prefix + i));
+            i += 1;
+        } else {
+            edits.push(Edit::Added(b[j]));
+            j += 1;
+        }
+    }
+    let (old_end, new_end) = (prefix + a.len(), prefix + b.len());
+    edits.extend((0..suffix).map(|k| Edit::Preserved(old_end + k, new[new_end + k])));
+    edits
+}
@@ -0,0 +1,127 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Splitting command lines into words the way POSIX shells do, without any
+//! of the expansions
+//!
+//! This isn't built on `Tokenizer::set_quotes` and `Tokenizer::set_escape`,
+//! since their escape escapes any char, in any quote, and they keep the
+//! quotes in the token. A shell keeps a backslash in single quotes as it is, and in
+//! double quotes only lets it escape `$`, `` ` ``, `"`, `\` and newlines. It
+//! also drops the quotes, so that `''` is an empty word, and has comments
+//! and lines continued by a backslash.
+//!
+//! ```
+//! use token::shell::shell_words;
+//!
+//! let line = r#"grep -e 'it'\''s' "my file.txt" \
+//!     --color=auto # highlight"#;
+//! assert_eq!(vec!["grep", "-e", "it's", "my file.txt", "--color=auto"],
+//!            shell_words(line).unwrap());
+//! ```
+
+use std::error;
+use std::fmt;
+
+/// A quote that was never closed, which is the only way a command line can
+/// fail to split
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenQuote {
+    /// The quote, `'` or `"`
+    pub quote: char,
+    /// The offset of the quote in the line
+    pub byte_offset: usize,
+}
+
+impl fmt::Display for OpenQuote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "quote {:?} at byte {} is never closed", self.quote, self.byte_offset)
+    }
+}
+
+impl error::Error for OpenQuote {}
+
+/// Splits a command line into words on spaces, tabs and newlines
+///
+/// - Single quotes keep everything up to the next single quote as it is.
+/// - Double quotes do the same, except that a backslash escapes `$`, `` ` ``,
+///   `"`, `\` and newlines, and is kept before anything else.
+/// - Outside of quotes, a backslash escapes any char.
+/// - An escaped newline is left out, so a line can be continued on the next.
+/// - A `#` at the start of a word starts a comment, up to the end of the line.
+///
+/// Quoted parts and the text around them are one word, and a quoted empty
+/// string is an empty word.
+///
+/// ```
+/// use token::shell::{shell_words, OpenQuote};
+///
+/// assert_eq!(vec!["a b", "", "c\\d", "\"e\""], shell_words(r#"a\ b '' "c\d" \"e\""#).unwrap());
+/// assert_eq!(Err(OpenQuote { quote: '"', byte_offset: 5 }), shell_words("echo \"hi"));
+/// ```
+pub fn shell_words(line: &str) -> Result<Vec<String>, OpenQuote> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Whether a word was started, which it can be without any chars
+    let mut in_word = false;
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_word {
+                    words.push(word.clone());
+                    word.clear();
+                    in_word = false;
+                }
+            }
+            '#' if !in_word => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, escaped)) => {
+                    word.push(escaped);
+                    in_word = true;
+                }
+                None => {
+                    word.push('\\');
+                    in_word = true;
+                }
+            },
+            '\'' | '"' => {
+                in_word = true;
+                loop {
+                    let next = match chars.next() {
+                        Some((_, next)) => next,
+                        None => return Err(OpenQuote { quote: c, byte_offset: i }),
+                    };
+                    if next == c {
+                        break;
+                    }
+                    if c == '"' && next == '\\' {
+                        match chars.peek() {
+                            Some(&(_, '\n')) => {
+                                chars.next();
+                                continue;
+                            }
+                            Some(&(_, escaped)) if "$`\"\\".contains(escaped) => {
+                                chars.next();
+                                word.push(escaped);
+                                continue;
+                            }
+                            _ => {}
+                        }
+                    }
+                    word.push(next);
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
@@ -0,0 +1,146 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Iterator adapters for generating word2vec-style training pairs from a
+//! stream of tokens
+//!
+//! ```
+//! use token::skipgrams::SkipGrams;
+//!
+//! let mut tokenizer = token::Tokenizer::new("".as_bytes(), vec![' ']);
+//! let mut tokens = Vec::new();
+//! tokenizer.tokenize_into("the quick brown fox".as_bytes(), &mut tokens).unwrap();
+//!
+//! let pairs: Vec<_> = SkipGrams::new(tokens.into_iter(), 1).collect();
+//! assert_eq!(pairs, vec![
+//!     ("the".to_string(), "quick".to_string()),
+//!     ("quick".to_string(), "the".to_string()),
+//!     ("quick".to_string(), "brown".to_string()),
+//!     ("brown".to_string(), "quick".to_string()),
+//!     ("brown".to_string(), "fox".to_string()),
+//!     ("fox".to_string(), "brown".to_string()),
+//! ]);
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::gen::Rng;
+
+/// An iterator of (center, context) pairs for every token and each of the
+/// tokens at most `window` positions before or after it
+pub struct SkipGrams<I: Iterator> {
+    tokens: I,
+    window: usize,
+    buffer: VecDeque<I::Item>,
+    center: usize,
+    context: usize,
+    done: bool,
+}
+
+impl <I> SkipGrams<I> where I: Iterator, I::Item: Clone {
+    /// Creates a new skip-gram iterator over the given tokens
+    pub fn new(tokens: I, window: usize) -> SkipGrams<I> {
+        SkipGrams {
+            tokens,
+            window,
+            buffer: VecDeque::new(),
+            center: 0,
+            context: 0,
+            done: false,
+        }
+    }
+}
+
+impl <I> Iterator for SkipGrams<I> where I: Iterator, I::Item: Clone {
+    type Item = (I::Item, I::Item);
+
+    fn next(&mut self) -> Option<(I::Item, I::Item)> {
+        loop {
+            // Read ahead until the whole window after the center is known
+            while !self.done && self.buffer.len() <= self.center + self.window {
+                match self.tokens.next() {
+                    Some(token) => self.buffer.push_back(token),
+                    None => self.done = true,
+                }
+            }
+            if self.center >= self.buffer.len() {
+                return None;
+            }
+            while self.context < self.buffer.len() && self.context <= self.center + self.window {
+                let context = self.context;
+                self.context += 1;
+                if context != self.center {
+                    return Some((self.buffer[self.center].clone(),
+                                 self.buffer[context].clone()));
+                }
+            }
+            // Move on to the next center, forgetting what's out of reach
+            self.center += 1;
+            if self.center > self.window {
+                self.buffer.pop_front();
+                self.center -= 1;
+            }
+            self.context = self.center.saturating_sub(self.window);
+        }
+    }
+}
+
+/// An iterator that randomly drops frequent tokens, keeping each with the
+/// probability `sqrt(threshold / frequency)` as in word2vec. Tokens without
+/// a count are always kept.
+///
+/// Putting it in front of `SkipGrams` makes pairs of rare words relatively
+/// more common, and widens the effective window around them.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use token::skipgrams::Subsample;
+///
+/// let mut counts = HashMap::new();
+/// counts.insert("the", 1000);
+/// counts.insert("aardvark", 1);
+///
+/// let tokens = vec!["the", "aardvark", "the", "the"];
+/// let kept: Vec<_> = Subsample::new(tokens.into_iter(), &counts, 1e-3, 42).collect();
+/// assert!(kept.contains(&"aardvark"));
+/// ```
+pub struct Subsample<'a, I: Iterator> where I::Item: 'a {
+    tokens: I,
+    counts: &'a HashMap<I::Item, usize>,
+    total: f64,
+    threshold: f64,
+    rng: Rng,
+}
+
+impl <'a, I> Subsample<'a, I> where I: Iterator, I::Item: Hash + Eq + 'a {
+    /// Creates a new subsampling iterator, using the given token counts (of
+    /// the whole corpus) and seed
+    pub fn new(tokens: I, counts: &'a HashMap<I::Item, usize>, threshold: f64,
+               seed: u64) -> Subsample<'a, I>
+    {
+        Subsample {
+            tokens,
+            counts,
+            total: counts.values().sum::<usize>() as f64,
+            threshold,
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl <'a, I> Iterator for Subsample<'a, I> where I: Iterator, I::Item: Hash + Eq + 'a {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        for token in &mut self.tokens {
+            let frequency = match self.counts.get(&token) {
+                Some(&count) if count > 0 => count as f64 / self.total,
+                _ => return Some(token),
+            };
+            if self.rng.next_f64() < (self.threshold / frequency).sqrt() {
+                return Some(token);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! A tokenizer of bytes from any source, which only needs `core` and
+//! `alloc`, for when the standard library isn't there, as on embedded
+//! devices reading text from a sensor or a serial port. Everything else in
+//! the crate, except `StrTokenizer`, needs the `std` feature, which is on by
+//! default.
+//!
+//! ```
+//! use token::source::SourceTokenizer;
+//!
+//! let source: &[u8] = b"T=21.5 H=40\r\nT=21.7 H=41\r\n";
+//! let mut tokenizer = SourceTokenizer::new(source, vec![' ', '\r', '\n']);
+//! assert_eq!(Ok(Some("T=21.5")), tokenizer.next());
+//! assert_eq!(Ok(Some("H=40")),   tokenizer.next());
+//! assert_eq!(Ok(Some("T=21.7")), tokenizer.next());
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::io::{BufRead, BufReader, Read};
+
+use crate::decode::{decode, sequence, Sequence};
+use crate::{InvalidUtf8, SeparatorSet};
+
+/// A source of bytes, read one at a time
+pub trait ByteSource {
+    /// The error of a failed read
+    type Error;
+
+    /// Returns the next byte, or `None` at the end of the input
+    fn next_byte(&mut self) -> Result<Option<u8>, Self::Error>;
+}
+
+impl ByteSource for &[u8] {
+    type Error = Infallible;
+
+    fn next_byte(&mut self) -> Result<Option<u8>, Infallible> {
+        match self.split_first() {
+            Some((&b, rest)) => {
+                *self = rest;
+                Ok(Some(b))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// A source of the bytes of a reader, which needs the `std` feature
+#[cfg(feature = "std")]
+pub struct ReadSource<R: Read> {
+    reader: BufReader<R>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> ReadSource<R> {
+    /// Creates a new source of the bytes of the reader
+    pub fn new(reader: R) -> ReadSource<R> {
+        ReadSource { reader: BufReader::new(reader) }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> ByteSource for ReadSource<R> {
+    type Error = io::Error;
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let b = match self.reader.fill_buf() {
+                Ok(buf) => buf.first().copied(),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if b.is_some() {
+                self.reader.consume(1);
+            }
+            return Ok(b);
+        }
+    }
+}
+
+/// The error of a `SourceTokenizer`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SourceError<E> {
+    /// The source failed
+    Source(E),
+    /// The input had a sequence that isn't valid UTF-8 at this byte offset,
+    /// and the policy for invalid input is `InvalidUtf8::Error`
+    InvalidUtf8(usize),
+}
+
+impl<E: fmt::Display> fmt::Display for SourceError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SourceError::Source(ref e) => e.fmt(f),
+            SourceError::InvalidUtf8(at) => write!(f, "invalid UTF-8 at byte {}", at),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> error::Error for SourceError<E> {}
+
+#[cfg(feature = "std")]
+impl From<SourceError<io::Error>> for io::Error {
+    fn from(error: SourceError<io::Error>) -> io::Error {
+        match error {
+            SourceError::Source(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+        }
+    }
+}
+
+/// A tokenizer of the bytes of a `ByteSource`, separating tokens on chars
+/// like `Tokenizer::new`
+pub struct SourceTokenizer<S: ByteSource> {
+    source: S,
+    lookup: SeparatorSet,
+    invalid_utf8: InvalidUtf8,
+    current: String,
+    /// A byte that was read, but belongs to the next char
+    pushed_back: Option<u8>,
+    offset: usize,
+}
+
+impl<S: ByteSource> SourceTokenizer<S> {
+    /// Creates a new tokenizer from a source and a set of separating chars
+    pub fn new(source: S, separators: Vec<char>) -> SourceTokenizer<S> {
+        SourceTokenizer {
+            source,
+            lookup: SeparatorSet::new(&separators),
+            invalid_utf8: InvalidUtf8::Error,
+            current: String::new(),
+            pushed_back: None,
+            offset: 0,
+        }
+    }
+
+    /// Sets what to do with input that is not valid UTF-8. By default it is
+    /// an error.
+    ///
+    /// ```
+    /// use token::InvalidUtf8;
+    /// use token::source::{SourceError, SourceTokenizer};
+    ///
+    /// let source: &[u8] = b"ok \xff";
+    /// let mut tokenizer = SourceTokenizer::new(source, vec![' ']);
+    /// assert_eq!(Ok(Some("ok")), tokenizer.next());
+    /// assert_eq!(Err(SourceError::InvalidUtf8(3)), tokenizer.next());
+    ///
+    /// let mut tokenizer = SourceTokenizer::new(source, vec![' ']);
+    /// tokenizer.set_invalid_utf8(InvalidUtf8::Replace);
+    /// assert_eq!(Ok(Some("ok")),       tokenizer.next());
+    /// assert_eq!(Ok(Some("\u{FFFD}")), tokenizer.next());
+    /// ```
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.invalid_utf8 = policy;
+    }
+
+    /// Returns the source
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    /// Returns the next non-empty token
+    #[allow(clippy::should_implement_trait)] // The token borrows the tokenizer
+    pub fn next(&mut self) -> Result<Option<&str>, SourceError<S::Error>> {
+        self.current.clear();
+        while let Some(c) = self.next_char()? {
+            if !self.lookup.contains(c) {
+                self.current.push(c);
+            } else if !self.current.is_empty() {
+                break;
+            }
+        }
+        if self.current.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(&self.current))
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, SourceError<S::Error>> {
+        let b = match self.pushed_back.take() {
+            Some(b) => Some(b),
+            None => self.source.next_byte().map_err(SourceError::Source)?,
+        };
+        if b.is_some() {
+            self.offset += 1;
+        }
+        Ok(b)
+    }
+
+    /// Decodes the next char, handling invalid sequences as `String::from_utf8_lossy`
+    /// does
+    fn next_char(&mut self) -> Result<Option<char>, SourceError<S::Error>> {
+        loop {
+            let mut bytes = [0; 4];
+            bytes[0] = match self.next_byte()? {
+                Some(b) if b < 0x80 => return Ok(Some(char::from(b))),
+                Some(b) => b,
+                None => return Ok(None),
+            };
+            let mut len = 1;
+            loop {
+                match sequence(&bytes[..len]) {
+                    Sequence::Valid(len) => return Ok(Some(decode(&bytes[..len]))),
+                    Sequence::Incomplete => match self.next_byte()? {
+                        Some(b) => {
+                            bytes[len] = b;
+                            len += 1;
+                        }
+                        None => break,
+                    },
+                    Sequence::Invalid => {
+                        // The last byte may start the next char
+                        if len > 1 {
+                            len -= 1;
+                            self.pushed_back = Some(bytes[len]);
+                            self.offset -= 1;
+                        }
+                        break;
+                    }
+                }
+            }
+            match self.invalid_utf8 {
+                InvalidUtf8::Error => return Err(SourceError::InvalidUtf8(self.offset - len)),
+                InvalidUtf8::Replace => return Ok(Some(char::REPLACEMENT_CHARACTER)),
+                InvalidUtf8::Skip => {}
+            }
+        }
+    }
+}
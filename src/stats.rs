@@ -0,0 +1,111 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Counting the tokens and sentences of a corpus as it streams by
+//!
+//! ```
+//! use token::stats::Statistics;
+//!
+//! let text = "The cat sat. The dog sat on the cat.";
+//! let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+//!
+//! let mut statistics = Statistics::new();
+//! statistics.collect(&mut splitter).unwrap();
+//! assert_eq!(9, statistics.tokens());
+//! assert_eq!(2, statistics.sentences());
+//! assert_eq!(8, statistics.unique_tokens());
+//! assert_eq!(4.5, statistics.average_sentence_len());
+//! assert_eq!(vec![("The", 2), ("cat", 1), ("cat.", 1)], statistics.top(3));
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+
+use crate::{Sentence, SentenceSplitter};
+
+/// Counts of the tokens and sentences seen so far. Tokens are counted as
+/// they are, so `The` and `the` are different tokens.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    tokens: usize,
+    sentences: usize,
+    /// The number of tokens that were part of a sentence
+    sentence_tokens: usize,
+    counts: HashMap<String, usize>,
+}
+
+impl Statistics {
+    /// Creates a new collector, with nothing counted
+    pub fn new() -> Statistics {
+        Statistics::default()
+    }
+
+    /// Counts a token that is not part of a sentence
+    pub fn add_token(&mut self, token: &str) {
+        self.tokens += 1;
+        match self.counts.get_mut(token) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert(token.to_string(), 1);
+            }
+        }
+    }
+
+    /// Counts a sentence and its tokens
+    pub fn add_sentence(&mut self, sentence: &Sentence<'_>) {
+        self.sentences += 1;
+        self.sentence_tokens += sentence.len();
+        for token in sentence.tokens() {
+            self.add_token(token);
+        }
+    }
+
+    /// Counts the rest of the sentences of a splitter
+    pub fn collect<R: Read>(&mut self, splitter: &mut SentenceSplitter<'_, R>) -> io::Result<()> {
+        while let Some(sentence) = splitter.next_sentence()? {
+            self.add_sentence(&sentence);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of tokens
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
+    /// Returns the number of sentences
+    pub fn sentences(&self) -> usize {
+        self.sentences
+    }
+
+    /// Returns the number of different tokens
+    pub fn unique_tokens(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns the average number of tokens in a sentence, or 0 if there
+    /// were no sentences
+    pub fn average_sentence_len(&self) -> f64 {
+        if self.sentences == 0 {
+            return 0.0;
+        }
+        self.sentence_tokens as f64 / self.sentences as f64
+    }
+
+    /// Returns how many times a token was seen
+    pub fn count(&self, token: &str) -> usize {
+        self.counts.get(token).cloned().unwrap_or(0)
+    }
+
+    /// Returns the `n` most frequent tokens with their counts, most frequent
+    /// first, and tokens that are as frequent in order
+    pub fn top(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut top: Vec<(&str, usize)> = self.counts.iter()
+            .map(|(token, &count)| (token.as_str(), count))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+}
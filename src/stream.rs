@@ -0,0 +1,215 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! A tokenizer for asynchronous readers, so that input arriving over a
+//! network can be tokenized without blocking. This module needs the
+//! `futures` feature, and works with any runtime through the `AsyncRead`
+//! trait of the `futures` crates. Readers of Tokio can be adapted to it with
+//! the `compat` module of `tokio-util`.
+//!
+//! ```
+//! use token::stream::AsyncTokenizer;
+//!
+//! let source: &[u8] = b"  Hello world \n  How do you do";
+//!
+//! let mut tokenizer = AsyncTokenizer::new(source, vec![' ', '\n']);
+//! futures_executor::block_on(async {
+//!     assert_eq!(Some("Hello"), tokenizer.next().await.unwrap());
+//!     assert_eq!(Some("world"), tokenizer.next().await.unwrap());
+//!     assert_eq!(Some("How"),   tokenizer.next().await.unwrap());
+//! });
+//! ```
+
+use std::future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use futures_io::AsyncRead;
+
+use crate::push::TokenizerState;
+use crate::{InvalidUtf8, Limits};
+
+/// A tokenizer of an asynchronous reader, separating tokens on chars like
+/// `Tokenizer::new`
+///
+/// The input is read in chunks, and a token is returned once the separator
+/// after it, or the end of the input, has been read.
+pub struct AsyncTokenizer<R: AsyncRead + Unpin> {
+    reader: R,
+    state: TokenizerState,
+    chunk: Vec<u8>,
+    ended: bool,
+    /// Where the token that is being read starts, from `pos`
+    start: Option<usize>,
+    /// How far the token has been scanned for a separator, from `pos`
+    scanned: usize,
+}
+
+impl<R: AsyncRead + Unpin> AsyncTokenizer<R> {
+    /// Creates a new tokenizer from a reader and a set of separating chars
+    pub fn new(reader: R, separators: Vec<char>) -> AsyncTokenizer<R> {
+        AsyncTokenizer {
+            reader,
+            state: TokenizerState::new(separators),
+            chunk: vec![0; 8 * 1024],
+            ended: false,
+            start: None,
+            scanned: 0,
+        }
+    }
+
+    /// Sets what to do with input that is not valid UTF-8, as with
+    /// `Tokenizer::set_invalid_utf8`
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8) {
+        self.state.set_invalid_utf8(policy);
+    }
+
+    /// Sets limits on how much input is read and how much of a token is
+    /// buffered, as with `Tokenizer::set_limits`. The limits on a single call
+    /// don't apply.
+    ///
+    /// ```
+    /// use token::{LimitExceeded, Limits};
+    /// use token::stream::AsyncTokenizer;
+    ///
+    /// let source: &[u8] = b"short averyveryverylongtoken";
+    /// let mut tokenizer = AsyncTokenizer::new(source, vec![' ']);
+    /// tokenizer.set_limits(Limits { max_buffered: Some(10), .. Limits::default() });
+    /// futures_executor::block_on(async {
+    ///     assert_eq!(Some("short"), tokenizer.next().await.unwrap());
+    ///     let e = tokenizer.next().await.unwrap_err();
+    ///     let limit = e.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+    ///     assert_eq!(Some(&LimitExceeded::Buffered(10)), limit);
+    /// });
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.state.set_limits(limits);
+    }
+
+    /// Returns the next token
+    #[allow(clippy::should_implement_trait)] // The token borrows the tokenizer
+    pub async fn next(&mut self) -> io::Result<Option<&str>> {
+        match future::poll_fn(|cx| self.poll_token(cx)).await? {
+            Some((start, end)) => Ok(Some(&self.state.text[start..end])),
+            None => Ok(None),
+        }
+    }
+
+    /// Turns the tokenizer into a stream of owned tokens, which ends after
+    /// the first error
+    ///
+    /// ```
+    /// use token::stream::AsyncTokenizer;
+    ///
+    /// let source: &[u8] = b"one two  three";
+    /// let tokens = AsyncTokenizer::new(source, vec![' ']).owned();
+    /// let tokens: Vec<String> = futures_executor::block_on_stream(tokens)
+    ///     .map(|token| token.unwrap())
+    ///     .collect();
+    /// assert_eq!(vec!["one", "two", "three"], tokens);
+    /// ```
+    pub fn owned(self) -> OwnedTokens<R> {
+        OwnedTokens { tokenizer: self, failed: false }
+    }
+
+    /// Finds the bounds of the next token in the decoded text, reading more
+    /// input until the token is known to be complete. The text read before
+    /// is not scanned again.
+    fn poll_token(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<(usize, usize)>>> {
+        loop {
+            let state = &self.state;
+            let rest = &state.text[state.pos..];
+            if self.start.is_none() {
+                self.start = rest.char_indices().find(|&(_, c)| !state.lookup.contains(c)).map(|(i, _)| i);
+                self.scanned = self.start.unwrap_or(0);
+            }
+            if let Some(start) = self.start {
+                let end = rest[self.scanned..].char_indices()
+                    .find(|&(_, c)| state.lookup.contains(c))
+                    .map(|(i, _)| self.scanned + i);
+                if let Err(e) = state.limits.check_buffered(end.unwrap_or(rest.len()) - start) {
+                    return Poll::Ready(Err(e));
+                }
+                match end {
+                    Some(end) => return self.take(start, end),
+                    None if self.ended => return self.take(start, rest.len()),
+                    None => self.scanned = rest.len(),
+                }
+            } else {
+                // Only separators are left, and they aren't needed
+                self.state.pos = self.state.text.len();
+                if self.ended {
+                    return Poll::Ready(Ok(None));
+                }
+            }
+            match self.poll_fill(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Returns the bounds of a token at the given offsets from `pos`, and
+    /// moves past it
+    fn take(&mut self, start: usize, end: usize) -> Poll<io::Result<Option<(usize, usize)>>> {
+        let bounds = (self.state.pos + start, self.state.pos + end);
+        self.state.pos = bounds.1;
+        self.start = None;
+        Poll::Ready(Ok(Some(bounds)))
+    }
+
+    /// Reads another chunk of input and decodes what it can of it, dropping
+    /// the text that has already been returned
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let read = match Pin::new(&mut self.reader).poll_read(cx, &mut self.chunk) {
+            Poll::Ready(Ok(read)) => read,
+            Poll::Ready(Err(ref e)) if e.kind() == io::ErrorKind::Interrupted => {
+                return Poll::Ready(Ok(()));
+            }
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+        if read == 0 {
+            self.ended = true;
+        }
+        Poll::Ready(self.state.feed(&self.chunk[..read], self.ended))
+    }
+}
+
+/// A stream of the tokens of an `AsyncTokenizer` as owned strings, returned
+/// by `AsyncTokenizer::owned`
+pub struct OwnedTokens<R: AsyncRead + Unpin> {
+    tokenizer: AsyncTokenizer<R>,
+    failed: bool,
+}
+
+impl<R: AsyncRead + Unpin> OwnedTokens<R> {
+    /// Returns the tokenizer
+    pub fn into_inner(self) -> AsyncTokenizer<R> {
+        self.tokenizer
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for OwnedTokens<R> {
+    type Item = io::Result<String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<io::Result<String>>> {
+        if self.failed {
+            return Poll::Ready(None);
+        }
+        let tokenizer = &mut self.tokenizer;
+        match tokenizer.poll_token(cx) {
+            Poll::Ready(Ok(Some((start, end)))) => {
+                Poll::Ready(Some(Ok(tokenizer.state.text[start..end].to_string())))
+            }
+            Poll::Ready(Ok(None)) => Poll::Ready(None),
+            Poll::Ready(Err(e)) => {
+                self.failed = true;
+                Poll::Ready(Some(Err(e)))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
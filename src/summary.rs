@@ -0,0 +1,186 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Extractive summaries: picking the most important sentences of a document
+//!
+//! ```
+//! use token::summary::{summarize, Scoring};
+//!
+//! let text = "Tokenizers split text into tokens. The weather was nice today. \
+//!             A tokenizer reads text and returns tokens. Splitters use tokenizers.";
+//! let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+//! let mut sentences = Vec::new();
+//! while let Some(sentence) = splitter.next().unwrap() {
+//!     sentences.push(sentence.to_string());
+//! }
+//!
+//! let stopwords = ["the", "a", "and", "into", "was"];
+//! let summary = summarize(&sentences, &stopwords, 2, Scoring::Frequency);
+//! let picked: Vec<usize> = summary.iter().map(|s| s.index).collect();
+//! assert_eq!(vec![0, 2], picked);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::io::Read;
+
+use crate::{split_word, SentenceSplitter, Span};
+
+/// How sentences are scored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scoring {
+    /// The average frequency of the words of the sentence in the document,
+    /// relative to the most frequent word
+    Frequency,
+    /// The rank of the sentence in a graph of sentences, connected by how
+    /// many words they share
+    TextRank,
+    /// The position of the sentence, with earlier sentences scoring higher,
+    /// as documents such as news articles tend to start with what matters
+    /// most
+    Position,
+}
+
+/// A sentence picked for a summary
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scored {
+    /// The index of the sentence in the document
+    pub index: usize,
+    /// The score of the sentence, only comparable with other scores from
+    /// the same document
+    pub score: f64,
+    /// Where the sentence is in the input, if it was read from a splitter
+    pub span: Option<Span>,
+}
+
+/// The damping factor of TextRank, as in PageRank
+const DAMPING: f64 = 0.85;
+
+/// Returns the lowercased words of a sentence, without stopwords
+fn words(sentence: &str, stopwords: &HashSet<String>) -> Vec<String> {
+    sentence.split_whitespace()
+        .map(|token| split_word(token).1.to_lowercase())
+        .filter(|word| !word.is_empty() && !stopwords.contains(word))
+        .collect()
+}
+
+fn frequency_scores(sentences: &[Vec<String>]) -> Vec<f64> {
+    let mut counts = HashMap::new();
+    for word in sentences.iter().flatten() {
+        *counts.entry(&word[..]).or_insert(0) += 1;
+    }
+    let max = counts.values().cloned().max().unwrap_or(1) as f64;
+    sentences.iter().map(|words| {
+        if words.is_empty() {
+            return 0.0;
+        }
+        let sum = words.iter().fold(0.0, |sum, word| sum + counts[&word[..]] as f64 / max);
+        sum / words.len() as f64
+    }).collect()
+}
+
+fn textrank_scores(sentences: &[Vec<String>]) -> Vec<f64> {
+    let sets: Vec<HashSet<&str>> = sentences.iter()
+        .map(|words| words.iter().map(|word| &word[..]).collect())
+        .collect();
+    // Sentences are similar when they share many words, relative to their
+    // lengths
+    let mut weights = vec![vec![0.0; sets.len()]; sets.len()];
+    for i in 0..sets.len() {
+        for j in i + 1..sets.len() {
+            let norm = (sets[i].len() as f64).ln() + (sets[j].len() as f64).ln();
+            if norm > 0.0 {
+                let shared = sets[i].intersection(&sets[j]).count() as f64;
+                weights[i][j] = shared / norm;
+                weights[j][i] = shared / norm;
+            }
+        }
+    }
+    let totals: Vec<f64> = weights.iter().map(|row| row.iter().fold(0.0, |sum, w| sum + w)).collect();
+
+    let mut ranks = vec![1.0; sets.len()];
+    for _ in 0..100 {
+        let next: Vec<f64> = (0..sets.len()).map(|i| {
+            let sum = (0..sets.len()).filter(|&j| totals[j] > 0.0).fold(0.0, |sum, j| {
+                sum + weights[j][i] / totals[j] * ranks[j]
+            });
+            1.0 - DAMPING + DAMPING * sum
+        }).collect();
+        let change = ranks.iter().zip(&next).fold(0.0, |max: f64, (r, n)| max.max((r - n).abs()));
+        ranks = next;
+        if change < 1e-6 {
+            break;
+        }
+    }
+    ranks
+}
+
+fn position_scores(sentences: &[Vec<String>]) -> Vec<f64> {
+    let len = sentences.len() as f64;
+    (0..sentences.len()).map(|index| (len - index as f64) / len).collect()
+}
+
+/// Scores the sentences of a document and returns the best `k` of them in
+/// the order they appear in. Among equally good sentences, the earlier ones
+/// are picked.
+pub fn summarize<I>(sentences: I, stopwords: &[&str], k: usize, scoring: Scoring) -> Vec<Scored>
+    where I: IntoIterator, I::Item: AsRef<str>
+{
+    let stopwords: HashSet<String> = stopwords.iter().map(|w| w.to_lowercase()).collect();
+    let sentences: Vec<Vec<String>> = sentences.into_iter()
+        .map(|sentence| words(sentence.as_ref(), &stopwords))
+        .collect();
+    pick(&sentences, k, scoring)
+}
+
+/// Summarizes the rest of the sentences of a splitter as `summarize` does,
+/// with the span of each sentence that is picked
+///
+/// ```
+/// use token::summary::{summarize_splitter, Scoring};
+///
+/// let text = "Rain is coming. Take a coat. It will pass.";
+/// let tokenizer = token::Tokenizer::new(text.as_bytes(), vec![' ']);
+/// let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+///
+/// let summary = summarize_splitter(&mut splitter, &[], 1, Scoring::Position).unwrap();
+/// assert_eq!(0, summary[0].index);
+/// let span = summary[0].span.unwrap();
+/// assert_eq!("Rain is coming.", &text[span.byte_start..span.byte_end]);
+/// ```
+pub fn summarize_splitter<R: Read>(splitter: &mut SentenceSplitter<'_, R>, stopwords: &[&str],
+                                   k: usize, scoring: Scoring) -> io::Result<Vec<Scored>> {
+    let stopwords: HashSet<String> = stopwords.iter().map(|w| w.to_lowercase()).collect();
+    let mut sentences = Vec::new();
+    let mut spans = Vec::new();
+    while let Some((sentence, span)) = splitter.next_with_span()? {
+        sentences.push(words(sentence, &stopwords));
+        spans.push(span);
+    }
+    let mut summary = pick(&sentences, k, scoring);
+    for scored in &mut summary {
+        scored.span = Some(spans[scored.index]);
+    }
+    Ok(summary)
+}
+
+/// Scores the words of each sentence and returns the best `k` sentences in
+/// the order they appear in
+fn pick(sentences: &[Vec<String>], k: usize, scoring: Scoring) -> Vec<Scored> {
+    let scores = match scoring {
+        Scoring::Frequency => frequency_scores(sentences),
+        Scoring::TextRank => textrank_scores(sentences),
+        Scoring::Position => position_scores(sentences),
+    };
+    let mut summary: Vec<Scored> = scores.into_iter().enumerate()
+        .map(|(index, score)| Scored { index, score, span: None })
+        .collect();
+    summary.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
+            .then_with(|| a.index.cmp(&b.index))
+    });
+    summary.truncate(k);
+    summary.sort_by_key(|a| a.index);
+    summary
+}
@@ -0,0 +1,123 @@
+// Copyright 2015 Jakob Lautrup Nysom
+
+//! Truecasing: restoring the usual capitalization of the words in sentences
+//! that were written in all caps, or all in lower case
+//!
+//! ```
+//! use token::truecase::Truecaser;
+//!
+//! let corpus = "I met Anna in Paris. Later, Anna and I went to the NASA museum.";
+//! let tokenizer = token::Tokenizer::new(corpus.as_bytes(), vec![' ']);
+//! let mut splitter = token::SentenceSplitter::new(tokenizer, vec!["."], vec![]);
+//!
+//! let mut truecaser = Truecaser::new();
+//! truecaser.learn_from(&mut splitter).unwrap();
+//!
+//! assert_eq!("Anna went to Paris!", truecaser.truecase("ANNA WENT TO PARIS!"));
+//! assert_eq!("Later I met the NASA people", truecaser.truecase("later i met the nasa people"));
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+
+use crate::{split_word, SentenceSplitter};
+
+/// How often each word was seen written in each way
+///
+/// Only words that are not first in their sentence are counted, since the
+/// first word is capitalized no matter what it is, and sentences without
+/// any lower case letters are ignored. The sentences learned from should be
+/// normally cased, and separated by single spaces, as returned by a
+/// `SentenceSplitter`.
+#[derive(Clone, Debug, Default)]
+pub struct Truecaser {
+    forms: HashMap<String, HashMap<String, usize>>,
+}
+
+impl Truecaser {
+    /// Creates a new truecaser without any statistics
+    pub fn new() -> Truecaser {
+        Truecaser::default()
+    }
+
+    /// Counts the capitalization of the words of a sentence
+    pub fn learn(&mut self, sentence: &str) {
+        if !sentence.chars().any(|c| c.is_lowercase()) {
+            return;
+        }
+        for token in sentence.split(' ').skip(1) {
+            let word = split_word(token).1;
+            if word.is_empty() {
+                continue;
+            }
+            let forms = self.forms.entry(word.to_lowercase()).or_default();
+            *forms.entry(word.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Counts the capitalization of the words of all the remaining
+    /// sentences of a splitter
+    pub fn learn_from<R: Read>(&mut self, splitter: &mut SentenceSplitter<R>)
+        -> io::Result<()>
+    {
+        while let Some(sentence) = splitter.next()? {
+            self.learn(sentence);
+        }
+        Ok(())
+    }
+
+    /// Returns the most common way of writing a word, if it has been seen.
+    /// Ties are broken by picking the form that sorts first.
+    ///
+    /// ```
+    /// let mut truecaser = token::truecase::Truecaser::new();
+    /// truecaser.learn("The Rust book and the rust on the bike, says Rust.");
+    /// assert_eq!(Some("Rust"), truecaser.form("RUST"));
+    /// assert_eq!(None, truecaser.form("bike?"));
+    /// ```
+    pub fn form(&self, word: &str) -> Option<&str> {
+        let forms = self.forms.get(&word.to_lowercase())?;
+        let mut best: Option<(&str, usize)> = None;
+        for (form, &count) in forms {
+            best = match best {
+                Some((b, c)) if c > count || (c == count && b <= &form[..]) => Some((b, c)),
+                _ => Some((form, count)),
+            };
+        }
+        best.map(|(form, _)| form)
+    }
+
+    /// Returns the sentence with every known word written the most common
+    /// way, and the first word capitalized. Unknown words are lowercased if
+    /// the sentence is in all caps, and kept as they are otherwise.
+    pub fn truecase(&self, sentence: &str) -> String {
+        let shouting = !sentence.chars().any(|c| c.is_lowercase());
+        let mut cased = String::with_capacity(sentence.len());
+        let mut first = true;
+        for (i, token) in sentence.split(' ').enumerate() {
+            if i > 0 {
+                cased.push(' ');
+            }
+            let (before, word, after) = split_word(token);
+            cased.push_str(before);
+            let word = match self.form(word) {
+                Some(form) => form.to_string(),
+                None if shouting => word.to_lowercase(),
+                None => word.to_string(),
+            };
+            let mut chars = word.chars();
+            if let Some(c) = chars.next() {
+                if first {
+                    cased.extend(c.to_uppercase());
+                    first = false;
+                } else {
+                    cased.push(c);
+                }
+                cased.push_str(chars.as_str());
+            }
+            cased.push_str(after);
+        }
+        cased
+    }
+}